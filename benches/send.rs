@@ -0,0 +1,147 @@
+// Measures sending logs over HTTP to a local server, i.e. the overhead of the
+// sink itself, and serializing request bodies alone, where the network doesn't
+// hide the cost.
+//
+// Allocations on the benchmarking thread are counted and printed per iteration,
+// the HTTP client and the server allocate on their own threads.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    hint::black_box,
+    sync::Arc,
+};
+
+use bytes::{BufMut, Bytes, BytesMut};
+use criterion::{Criterion, criterion_group, criterion_main};
+use serde_json::json;
+use spdlog::{
+    Logger,
+    formatter::{PatternFormatter, pattern},
+    info,
+};
+use spdlog_telegram::TelegramSink;
+
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<u64> = const { Cell::new(0) };
+}
+
+fn count_allocation() {
+    // The thread-local is gone while the thread exits.
+    _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count_allocation();
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count_allocation();
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+// Prints the average number of allocations per call of `f`, after a warm-up.
+fn report_allocations(name: &str, mut f: impl FnMut()) {
+    const ITERATIONS: u64 = 100;
+    f();
+    let before = ALLOCATIONS.with(Cell::get);
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    let allocations = ALLOCATIONS.with(Cell::get) - before;
+    println!(
+        "{name}: {:.1} allocations per iteration",
+        allocations as f64 / ITERATIONS as f64
+    );
+}
+
+fn texts() -> [(&'static str, String); 2] {
+    [
+        ("short", "Hello Telegram!".to_string()),
+        ("max_len", "a\"<b>".repeat(4096 / 5)),
+    ]
+}
+
+fn send(c: &mut Criterion) {
+    let mut server = mockito::Server::new();
+    server
+        .mock("POST", mockito::Matcher::Any)
+        .with_body(r#"{"ok":true,"result":{}}"#)
+        .expect_at_least(0)
+        .create();
+    let logger = Logger::builder()
+        .error_handler(|err| panic!("error handler triggered: {err}"))
+        .sink(Arc::new(
+            TelegramSink::builder()
+                .server_url_str(&server.url())
+                .unwrap()
+                .bot_token("1234567890:AbCdEfGhiJkLmNoPq1R2s3T4u5V6w7X8y9z")
+                .recipient(-1001234567890)
+                .formatter(PatternFormatter::new(pattern!("{payload}")))
+                .build()
+                .unwrap(),
+        ))
+        .build()
+        .unwrap();
+
+    let mut group = c.benchmark_group("send");
+    for (name, text) in texts() {
+        report_allocations(&format!("send/{name}"), || {
+            info!(logger: logger, "{}", black_box(&text));
+        });
+        group.bench_function(name, |b| {
+            b.iter(|| info!(logger: logger, "{}", black_box(&text)));
+        });
+    }
+    group.finish();
+}
+
+// The same as the HTTP transport does, i.e. serializing into a reused buffer
+// and splitting the body off.
+fn serialize_reused(buf: &mut BytesMut, payload: &serde_json::Value) -> Bytes {
+    buf.reserve(16 * 1024);
+    serde_json::to_writer(buf.writer(), payload).unwrap();
+    buf.split().freeze()
+}
+
+fn serialize(c: &mut Criterion) {
+    let mut group = c.benchmark_group("serialize");
+    for (name, text) in texts() {
+        let payload = json!({
+            "chat_id": -1001234567890i64,
+            "text": text,
+            "disable_notification": false,
+        });
+
+        report_allocations(&format!("serialize/{name}/to_string"), || {
+            black_box(payload.to_string());
+        });
+        group.bench_function(format!("{name}/to_string"), |b| {
+            b.iter(|| black_box(payload.to_string()));
+        });
+
+        let mut buf = BytesMut::new();
+        report_allocations(&format!("serialize/{name}/reused"), || {
+            black_box(serialize_reused(&mut buf, &payload));
+        });
+        group.bench_function(format!("{name}/reused"), |b| {
+            b.iter(|| black_box(serialize_reused(&mut buf, &payload)));
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, send, serialize);
+criterion_main!(benches);
@@ -0,0 +1,44 @@
+use std::{env, error::Error as StdError, process, sync::Arc};
+
+use spdlog::prelude::*;
+use spdlog_telegram::{Recipient, TelegramSink};
+
+fn main() {
+    let bot_token = env::var("BOT_TOKEN").unwrap_or_else(|_| {
+        error!("env var `BOT_TOKEN` is not set");
+        process::exit(1);
+    });
+
+    let Some(recipient) = env::args().nth(1).map(|input| {
+        input
+            .parse::<i64>()
+            .map_or_else(|_| Recipient::username(input), Recipient::chat_id)
+    }) else {
+        error!("invalid cli argument. usage: `async <chat_id | @username>`");
+        process::exit(1);
+    };
+
+    let logger = match build_logger(bot_token, recipient) {
+        Ok(logger) => logger,
+        Err(err) => {
+            error!("failed to build logger: {err}");
+            process::exit(1);
+        }
+    };
+
+    // Returns immediately, the message is sent in a background thread.
+    error!(logger: logger, "this will go to Telegram without blocking the current thread");
+
+    // Dropping the logger waits for pending logs to be sent.
+    drop(logger);
+}
+
+fn build_logger(bot_token: String, recipient: Recipient) -> Result<Logger, Box<dyn StdError>> {
+    let sink = TelegramSink::builder()
+        .bot_token(bot_token)
+        .recipient(recipient)
+        .build()?
+        // Wraps the sink in an `AsyncPoolSink`, so network I/O happens in a thread pool
+        .into_async_pool()?;
+    Ok(Logger::builder().sink(Arc::new(sink)).build()?)
+}
@@ -1,7 +1,7 @@
 use std::{env, error::Error as StdError, process, sync::Arc};
 
 use spdlog::prelude::*;
-use spdlog_telegram::{Recipient, TelegramSink};
+use spdlog_telegram::{NotifyPolicy, Recipient, TelegramSink};
 
 fn main() {
     let bot_token = env::var("BOT_TOKEN").unwrap_or_else(|_| {
@@ -34,12 +34,12 @@ fn setup_logger(bot_token: String, recipient: Recipient) -> Result<(), Box<dyn S
         TelegramSink::builder()
             .bot_token(bot_token)
             .recipient(recipient)
-            // Notification (sound)
-            //  - enabled for logs with level: critical, error;
-            //  - disabled for logs with level: warn, info, debug, trace.
-            .silence(LevelFilter::MoreVerboseEqual(Level::Warn))
-            // We don't care logs with level: info, debug, trace.
-            .level_filter(LevelFilter::MoreSevereEqual(Level::Warn))
+            // Sends logs with level: critical, error, warn.
+            // Notification (sound) is enabled for logs with level: critical, error.
+            .notify_policy(NotifyPolicy {
+                min_level_to_send: Level::Warn,
+                min_level_for_sound: Level::Error,
+            })
             .build()?,
     );
     let logger = spdlog::default_logger().fork_with(|logger| {
@@ -0,0 +1,107 @@
+use std::{
+    collections::VecDeque,
+    io,
+    sync::{Arc, mpsc},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use serde_json as json;
+use spdlog::sink::SinkProp;
+
+use crate::request::Requester;
+
+struct Pending {
+    due: Instant,
+    chat_id: json::Value,
+    message_id: u64,
+}
+
+// Deletes sent messages after a TTL in a background thread.
+//
+// Pending deletions are kept in memory only, they are abandoned when the sink
+// is dropped.
+pub(crate) struct AutoDeleter {
+    ttl: Duration,
+    sender: Option<mpsc::Sender<Pending>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl AutoDeleter {
+    pub(crate) fn new(
+        ttl: Duration,
+        requester: Arc<Requester>,
+        prop: Arc<SinkProp>,
+    ) -> io::Result<Self> {
+        let (sender, receiver) = mpsc::channel();
+        let thread = thread::Builder::new()
+            .name("spdlog-telegram-auto-delete".into())
+            .spawn(move || run(receiver, &requester, &prop))?;
+        Ok(Self {
+            ttl,
+            sender: Some(sender),
+            thread: Some(thread),
+        })
+    }
+
+    // Schedules the deletion of a sent message, i.e. the `result` of
+    // `sendMessage` or `forwardMessage`.
+    pub(crate) fn schedule(&self, sent: &json::Value) {
+        let (Some(chat_id), Some(message_id)) = (
+            sent.get("chat").and_then(|chat| chat.get("id")),
+            sent.get("message_id").and_then(|j| j.as_u64()),
+        ) else {
+            // e.g. in dry-run mode
+            return;
+        };
+        let pending = Pending {
+            due: Instant::now() + self.ttl,
+            chat_id: chat_id.clone(),
+            message_id,
+        };
+        if let Some(sender) = &self.sender {
+            _ = sender.send(pending);
+        }
+    }
+}
+
+impl Drop for AutoDeleter {
+    fn drop(&mut self) {
+        // Disconnects the channel to stop the thread.
+        self.sender.take();
+        if let Some(thread) = self.thread.take() {
+            _ = thread.join();
+        }
+    }
+}
+
+fn run(receiver: mpsc::Receiver<Pending>, requester: &Requester, prop: &SinkProp) {
+    // The TTL is fixed, so deletions are always due in the order they are
+    // received.
+    let mut queue = VecDeque::new();
+    loop {
+        let received = match queue.front() {
+            None => receiver
+                .recv()
+                .map_err(|_| mpsc::RecvTimeoutError::Disconnected),
+            Some(Pending { due, .. }) => {
+                receiver.recv_timeout(due.saturating_duration_since(Instant::now()))
+            }
+        };
+        match received {
+            Ok(pending) => queue.push_back(pending),
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => return,
+        }
+
+        let now = Instant::now();
+        while let Some(pending) = queue.front()
+            && pending.due <= now
+        {
+            let pending = queue.pop_front().unwrap();
+            if let Err(err) = requester.delete_message(&pending.chat_id, pending.message_id) {
+                prop.call_error_handler(spdlog::Error::Downstream(err.into()));
+            }
+        }
+    }
+}
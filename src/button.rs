@@ -0,0 +1,68 @@
+use serde_json::{self as json, json};
+
+/// Represents an inline keyboard button attached to a message.
+///
+/// See [`TelegramSinkBuilder::inline_buttons`] and [Telegram Bot API:
+/// InlineKeyboardButton][api].
+///
+/// ## Examples
+///
+/// ```
+/// use spdlog_telegram::Button;
+///
+/// let dashboard = Button::url("Open dashboard", "https://grafana.example.com/d/api");
+/// let ack = Button::callback("Acknowledge", "ack:web-3");
+/// ```
+///
+/// [`TelegramSinkBuilder::inline_buttons`]: crate::TelegramSinkBuilder::inline_buttons
+/// [api]: https://core.telegram.org/bots/api#inlinekeyboardbutton
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Button {
+    text: String,
+    action: Action,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Action {
+    Url(String),
+    CallbackData(String),
+}
+
+impl Button {
+    /// Constructs a `Button` opening the URL when pressed.
+    pub fn url<T, U>(text: T, url: U) -> Self
+    where
+        T: Into<String>,
+        U: Into<String>,
+    {
+        Self {
+            text: text.into(),
+            action: Action::Url(url.into()),
+        }
+    }
+
+    /// Constructs a `Button` sending the callback data to the bot when
+    /// pressed.
+    ///
+    /// This crate doesn't receive updates, so callback queries must be handled
+    /// by an external bot process using the same bot token. Otherwise, the
+    /// button keeps loading until it times out. Telegram requires the data to
+    /// be 1-64 bytes.
+    pub fn callback<T, D>(text: T, data: D) -> Self
+    where
+        T: Into<String>,
+        D: Into<String>,
+    {
+        Self {
+            text: text.into(),
+            action: Action::CallbackData(data.into()),
+        }
+    }
+
+    pub(crate) fn to_json(&self) -> json::Value {
+        match &self.action {
+            Action::Url(url) => json!({ "text": self.text, "url": url }),
+            Action::CallbackData(data) => json!({ "text": self.text, "callback_data": data }),
+        }
+    }
+}
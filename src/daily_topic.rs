@@ -0,0 +1,155 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use spdlog::Level;
+
+use crate::{Result, request::Requester};
+
+const SECS_PER_DAY: i64 = 24 * 60 * 60;
+
+// Creating a topic is not retried before this, so that a failing chat doesn't
+// make every log call `createForumTopic` and report the error again.
+const RETRY_DELAY: Duration = Duration::from_secs(60);
+
+// The topic of a chat, or the failure to create it.
+enum Topic {
+    // The day since the Unix epoch it was created on, and its thread ID. `None` in
+    // dry-run mode.
+    Created { day: i64, thread_id: Option<u64> },
+    Failed { retry_at: Instant },
+}
+
+// Creates a forum topic per day, named by the date in a fixed UTC offset.
+pub(crate) struct DailyTopic {
+    name_fmt: String,
+    // In seconds east of UTC
+    utc_offset: i32,
+    retry_delay: Duration,
+    // Each chat has its own lock, so that creating a topic never blocks logs to
+    // other chats.
+    topics: Mutex<HashMap<String, Arc<Mutex<Option<Topic>>>>>,
+}
+
+impl DailyTopic {
+    pub(crate) fn new(name_fmt: String, utc_offset: i32) -> Self {
+        Self {
+            name_fmt,
+            utc_offset,
+            retry_delay: RETRY_DELAY,
+            topics: Mutex::new(HashMap::new()),
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn set_retry_delay(&mut self, delay: Duration) {
+        self.retry_delay = delay;
+    }
+
+    // Returns the thread ID of the topic of today in the chat of the recipient for
+    // the level, creating the topic if needed. Returns `None` without retrying for
+    // a while after creating it failed.
+    pub(crate) fn thread_id(
+        &self,
+        requester: &Requester,
+        level: Option<Level>,
+    ) -> Result<Option<u64>> {
+        let day = self.day(SystemTime::now());
+        let chat_id = requester.chat_id_for(level);
+        let key = chat_id.to_string();
+
+        let topic = self
+            .topics
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .entry(key)
+            .or_default()
+            .clone();
+        // Held while creating, so that concurrent logs don't create the topic twice.
+        let mut topic = topic.lock().unwrap_or_else(|err| err.into_inner());
+        match *topic {
+            Some(Topic::Created {
+                day: created_on,
+                thread_id,
+            }) if created_on == day => return Ok(thread_id),
+            Some(Topic::Failed { retry_at }) if Instant::now() < retry_at => return Ok(None),
+            _ => {}
+        }
+        match requester.create_forum_topic(&chat_id, &self.name(day), None) {
+            Ok(thread_id) => {
+                *topic = Some(Topic::Created { day, thread_id });
+                Ok(thread_id)
+            }
+            Err(err) => {
+                *topic = Some(Topic::Failed {
+                    retry_at: Instant::now() + self.retry_delay,
+                });
+                Err(err)
+            }
+        }
+    }
+
+    fn day(&self, now: SystemTime) -> i64 {
+        let secs = match now.duration_since(UNIX_EPOCH) {
+            Ok(elapsed) => elapsed.as_secs() as i64,
+            Err(err) => -(err.duration().as_secs() as i64),
+        };
+        (secs + i64::from(self.utc_offset)).div_euclid(SECS_PER_DAY)
+    }
+
+    fn name(&self, day: i64) -> String {
+        self.name_fmt.replace("{date}", &format_date(day))
+    }
+}
+
+// Formats the day since the Unix epoch as `YYYY-MM-DD`.
+//
+// See http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+fn format_date(days: i64) -> String {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn format_date() {
+        assert_eq!(super::format_date(0), "1970-01-01");
+        assert_eq!(super::format_date(-1), "1969-12-31");
+        assert_eq!(super::format_date(19723), "2024-01-01");
+        assert_eq!(super::format_date(19782), "2024-02-29");
+        assert_eq!(super::format_date(20740), "2026-10-14");
+    }
+
+    #[test]
+    fn day() {
+        let at = |secs| UNIX_EPOCH + Duration::from_secs(secs);
+        let utc = DailyTopic::new("logs {date}".into(), 0);
+        let utc_plus_8 = DailyTopic::new("logs {date}".into(), 8 * 3600);
+        let utc_minus_5 = DailyTopic::new("logs {date}".into(), -5 * 3600);
+
+        // 1970-01-01T20:00:00Z
+        assert_eq!(utc.day(at(20 * 3600)), 0);
+        assert_eq!(utc_plus_8.day(at(20 * 3600)), 1);
+        assert_eq!(utc_minus_5.day(at(20 * 3600)), 0);
+        // 1970-01-02T02:00:00Z
+        assert_eq!(utc.day(at(26 * 3600)), 1);
+        assert_eq!(utc_minus_5.day(at(26 * 3600)), 0);
+
+        assert_eq!(utc_plus_8.name(1), "logs 1970-01-02");
+    }
+}
@@ -2,6 +2,8 @@ use std::fmt;
 
 use thiserror::Error;
 
+use crate::text;
+
 /// Represents errors that can occur in this crate.
 #[derive(Error, Debug)]
 pub enum Error {
@@ -9,21 +11,228 @@ pub enum Error {
     #[error("failed to parse URL: {0}")]
     ParseUrl(url::ParseError),
 
+    /// Returned when an invalid message link is used.
+    #[error("failed to parse message link: {0}")]
+    ParseMessageLink(String),
+
     /// Returned when sending an HTTP request fails.
     #[error("failed to send HTTP request: {0}")]
     SendRequest(ReqwestDesensitizedError),
 
     /// Returned when Telegram Bot API returns an error.
-    #[error("Telegram API error: {0:?}")]
-    TelegramApi(Option<String>),
+    #[error("Telegram API error: {description:?}{}", ParametersSummary(parameters))]
+    TelegramApi {
+        /// The human-readable description of the error.
+        description: Option<String>,
+        /// The [`parameters`] object of the response as-is, if present.
+        ///
+        /// It contains information to automatically handle the error, e.g.
+        /// `retry_after`. Fields added to the Bot API in the future are kept
+        /// too.
+        ///
+        /// [`parameters`]: https://core.telegram.org/bots/api#responseparameters
+        parameters: Option<serde_json::Value>,
+    },
+
+    /// Returned when the response is not a Bot API response at all, i.e. not
+    /// JSON or without the field `ok`.
+    ///
+    /// This usually indicates a problem between the sink and Telegram, e.g. a
+    /// proxy responding with an HTML error page, rather than an error of the
+    /// request itself.
+    #[error("unexpected response{}: {body_snippet:?}", StatusSummary(*status))]
+    UnexpectedResponse {
+        /// The HTTP status code, if delivered by the default HTTP transport.
+        status: Option<u16>,
+        /// The beginning of the response body, truncated for diagnosis.
+        body_snippet: String,
+    },
+
+    /// Returned when the recipient group has been migrated to a supergroup.
+    ///
+    /// All subsequent sends to the old chat ID will fail, the recipient should
+    /// be updated to the new chat ID, or see
+    /// [`TelegramSinkBuilder::follow_chat_migration`].
+    ///
+    /// [`TelegramSinkBuilder::follow_chat_migration`]: crate::TelegramSinkBuilder::follow_chat_migration
+    #[error("group chat has been migrated to a supergroup with chat ID {migrate_to_chat_id}")]
+    ChatMigrated {
+        /// The chat ID of the supergroup.
+        migrate_to_chat_id: i64,
+    },
+
+    /// Returned when the recipient user has blocked the bot.
+    ///
+    /// See [`TelegramSinkBuilder::on_blocked`].
+    ///
+    /// [`TelegramSinkBuilder::on_blocked`]: crate::TelegramSinkBuilder::on_blocked
+    #[error("bot was blocked by the recipient user")]
+    BotBlocked,
+
+    /// Returned when the recipient channel has no linked discussion group.
+    ///
+    /// See [`Recipient::linked_discussion`].
+    ///
+    /// [`Recipient::linked_discussion`]: crate::Recipient::linked_discussion
+    #[error("recipient channel has no linked discussion group")]
+    NoLinkedDiscussion,
+
+    /// Returned when the recipient chat is a forum, but no topic is
+    /// configured.
+    ///
+    /// See [`TelegramSinkBuilder::require_topic`].
+    ///
+    /// [`TelegramSinkBuilder::require_topic`]: crate::TelegramSinkBuilder::require_topic
+    #[error("recipient chat is a forum, but no topic is configured")]
+    TopicRequired,
+
+    /// Returned when the serialized request body exceeds the limit, before
+    /// sending it.
+    ///
+    /// See [`TelegramSinkBuilder::max_request_bytes`].
+    ///
+    /// [`TelegramSinkBuilder::max_request_bytes`]: crate::TelegramSinkBuilder::max_request_bytes
+    #[error("request body of {size} bytes exceeds the limit of {limit} bytes")]
+    RequestTooLarge {
+        /// The size of the serialized request body, in bytes.
+        size: usize,
+        /// The configured limit, in bytes.
+        limit: usize,
+    },
+
+    /// Returned when the formatted text is empty and [`EmptyPolicy::Error`] is
+    /// configured.
+    ///
+    /// [`EmptyPolicy::Error`]: crate::EmptyPolicy::Error
+    #[error("message text is empty")]
+    EmptyText,
+
+    /// Returned when writing a request in [dry-run mode] fails.
+    ///
+    /// [dry-run mode]: crate::TelegramSinkBuilder::dry_run
+    #[error("failed to write dry-run output: {0}")]
+    DryRun(std::io::Error),
+
+    /// Returned when spawning the background thread for [auto-deletion]
+    /// fails.
+    ///
+    /// [auto-deletion]: crate::TelegramSinkBuilder::auto_delete
+    #[error("failed to spawn the auto-delete thread: {0}")]
+    SpawnThread(std::io::Error),
+}
+
+impl Error {
+    // The maximum length of the body snippet, in UTF-16 code units.
+    const MAX_BODY_SNIPPET_LEN: usize = 256;
+
+    pub(crate) fn unexpected_response(status: Option<u16>, body: &str) -> Self {
+        let mut body_snippet = body.trim().to_string();
+        text::truncate_utf16(
+            &mut body_snippet,
+            Self::MAX_BODY_SNIPPET_LEN,
+            text::ELLIPSIS,
+        );
+        Self::UnexpectedResponse {
+            status,
+            body_snippet,
+        }
+    }
 }
 
 /// Represents the result type for this crate.
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// Represents an error from the HTTP client, with the URL removed.
+///
+/// The URL contains the bot token, so it's removed to avoid leaking the token
+/// into logs. Use the accessors to classify the error. The URL is kept only if
+/// [`TelegramSinkBuilder::desensitize_errors`] is disabled.
+///
+/// [`TelegramSinkBuilder::desensitize_errors`]: crate::TelegramSinkBuilder::desensitize_errors
 #[derive(Debug)]
 pub struct ReqwestDesensitizedError(reqwest::Error);
 
+impl ReqwestDesensitizedError {
+    // Keeps the URL, for local debugging only.
+    pub(crate) fn with_url(err: reqwest::Error) -> Self {
+        Self(err)
+    }
+
+    /// Returns `true` if the error is related to a timeout.
+    #[must_use]
+    pub fn is_timeout(&self) -> bool {
+        self.0.is_timeout()
+    }
+
+    /// Returns `true` if the error is related to connecting.
+    #[must_use]
+    pub fn is_connect(&self) -> bool {
+        self.0.is_connect()
+    }
+
+    /// Returns `true` if the error is related to the request.
+    #[must_use]
+    pub fn is_request(&self) -> bool {
+        self.0.is_request()
+    }
+
+    /// Returns `true` if the error is related to the request or response body.
+    #[must_use]
+    pub fn is_body(&self) -> bool {
+        self.0.is_body()
+    }
+
+    /// Returns `true` if the error is related to decoding the response body.
+    #[must_use]
+    pub fn is_decode(&self) -> bool {
+        self.0.is_decode()
+    }
+
+    /// Returns `true` if the error is related to redirects.
+    #[must_use]
+    pub fn is_redirect(&self) -> bool {
+        self.0.is_redirect()
+    }
+}
+
+// Summarizes the known fields of `ResponseParameters`, e.g. ` (retry_after=5)`.
+struct ParametersSummary<'a>(&'a Option<serde_json::Value>);
+
+impl fmt::Display for ParametersSummary<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(parameters) = self.0 else {
+            return Ok(());
+        };
+        let mut known = ["migrate_to_chat_id", "retry_after"]
+            .into_iter()
+            .filter_map(|key| parameters.get(key).map(|value| (key, value)))
+            .peekable();
+        if known.peek().is_none() {
+            return Ok(());
+        }
+        f.write_str(" (")?;
+        for (i, (key, value)) in known.enumerate() {
+            if i != 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{key}={value}")?;
+        }
+        f.write_str(")")
+    }
+}
+
+// Formats an optional HTTP status, e.g. ` (HTTP 502)`.
+struct StatusSummary(Option<u16>);
+
+impl fmt::Display for StatusSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(status) => write!(f, " (HTTP {status})"),
+            None => Ok(()),
+        }
+    }
+}
+
 impl From<reqwest::Error> for ReqwestDesensitizedError {
     fn from(value: reqwest::Error) -> Self {
         Self(value.without_url())
@@ -35,3 +244,56 @@ impl fmt::Display for ReqwestDesensitizedError {
         self.0.fmt(f)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn telegram_api_display() {
+        let error = |parameters| Error::TelegramApi {
+            description: Some("Too Many Requests: retry after 5".into()),
+            parameters,
+        };
+        assert_eq!(
+            error(None).to_string(),
+            r#"Telegram API error: Some("Too Many Requests: retry after 5")"#
+        );
+        assert_eq!(
+            error(Some(json!({ "retry_after": 5, "unknown": true }))).to_string(),
+            r#"Telegram API error: Some("Too Many Requests: retry after 5") (retry_after=5)"#
+        );
+        assert_eq!(
+            error(Some(json!({ "unknown": true }))).to_string(),
+            r#"Telegram API error: Some("Too Many Requests: retry after 5")"#
+        );
+    }
+
+    #[test]
+    fn unexpected_response() {
+        let error =
+            Error::unexpected_response(Some(502), &format!("<html>{}</html>\n", "a".repeat(300)));
+        let Error::UnexpectedResponse {
+            status,
+            body_snippet,
+        } = &error
+        else {
+            unreachable!()
+        };
+        assert_eq!(*status, Some(502));
+        assert_eq!(text::utf16_len(body_snippet), 256);
+        assert!(body_snippet.starts_with("<html>aaa") && body_snippet.ends_with("a…"));
+        assert!(
+            error
+                .to_string()
+                .starts_with(r#"unexpected response (HTTP 502): "<html>"#)
+        );
+
+        assert_eq!(
+            Error::unexpected_response(None, "{}").to_string(),
+            r#"unexpected response: "{}""#
+        );
+    }
+}
@@ -0,0 +1,335 @@
+use std::fmt::Write as _;
+
+use serde_json as json;
+use spdlog::{
+    Record, StringBuf,
+    formatter::{Formatter, FormatterContext},
+};
+
+use crate::{level_color, markup, text};
+
+/// Represents how the default formatter renders key-values.
+///
+/// See [`TelegramSinkBuilder::kv_style`].
+///
+/// [`TelegramSinkBuilder::kv_style`]: crate::TelegramSinkBuilder::kv_style
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum KvStyle {
+    /// Renders pairs after the payload, separated by spaces, e.g. `k1=v1
+    /// k2=v2`.
+    #[default]
+    Inline,
+    /// Renders each pair on its own line after the payload, e.g. `k1: v1`.
+    Lines,
+    /// Renders pairs as a JSON object after the payload, e.g.
+    /// `{"k1":"v1","k2":"v2"}`. Values are rendered as strings.
+    Json,
+    /// Renders no key-values.
+    Hidden,
+}
+
+// The default formatter of `TelegramSink`, equivalent to pattern
+// `"#log #{level} {payload} {kv}"` (the `#log` tag is customizable), preceded
+// by `"[{logger}] "` and followed by `"\n@{source}"` if enabled and available.
+//
+// It's implemented manually instead of using `PatternFormatter`, so that it can
+// be customized at runtime via `TelegramSinkBuilder`.
+//
+// With the HTML level badge, the level is rendered as `<b>{color} {LEVEL}</b>`
+// instead, and everything else is HTML-escaped.
+#[derive(Clone)]
+pub(crate) struct DefaultFormatter {
+    pub(crate) source: bool,
+    pub(crate) max_kv_value_len: Option<usize>,
+    pub(crate) logger_name: bool,
+    // Without the leading `#`
+    pub(crate) log_tag: Option<String>,
+    // Writes nothing but the payload if enabled
+    pub(crate) payload_only: bool,
+    pub(crate) ellipsis: String,
+    pub(crate) html_level_badge: bool,
+    pub(crate) kv_style: KvStyle,
+}
+
+impl DefaultFormatter {
+    pub(crate) fn new(source: bool) -> Self {
+        Self {
+            source,
+            max_kv_value_len: None,
+            logger_name: false,
+            log_tag: Some("log".into()),
+            payload_only: false,
+            ellipsis: text::ELLIPSIS.into(),
+            html_level_badge: false,
+            kv_style: KvStyle::default(),
+        }
+    }
+
+    // Returns the pattern this formatter is equivalent to, in the syntax of
+    // `pattern!`. The HTML level badge and key-value styles other than inline are
+    // not reflected, as there's no pattern for them.
+    pub(crate) fn pattern(&self) -> String {
+        if self.payload_only {
+            return "{payload}".into();
+        }
+        let mut pattern = String::new();
+        if self.logger_name {
+            pattern.push_str("[{logger}] ");
+        }
+        if let Some(tag) = &self.log_tag {
+            pattern.push('#');
+            pattern.push_str(&tag.replace('{', "{{").replace('}', "}}"));
+            pattern.push(' ');
+        }
+        pattern.push_str("#{level} {payload} {kv}");
+        if self.source {
+            pattern.push_str("\n@{source}");
+        }
+        pattern
+    }
+
+    // Writes plain text, escaping it if the output is HTML.
+    fn write_text(&self, dest: &mut StringBuf, text: &str) -> std::fmt::Result {
+        if self.html_level_badge {
+            dest.write_str(&markup::escape_html(text))
+        } else {
+            dest.write_str(text)
+        }
+    }
+
+    // Writes the key-values following the payload, including the separator from
+    // it.
+    fn write_kv(&self, record: &Record, dest: &mut StringBuf) -> std::fmt::Result {
+        let key_values = record.key_values();
+        let pairs = key_values.into_iter().map(|(key, value)| {
+            let mut value = value.to_string();
+            if let Some(max_len) = self.max_kv_value_len {
+                text::truncate_utf16(&mut value, max_len, &self.ellipsis);
+            }
+            (key, value)
+        });
+        match self.kv_style {
+            KvStyle::Inline => {
+                // The separator is written even without key-values, as `{kv}` in a
+                // pattern would.
+                dest.write_char(' ')?;
+                for (i, (key, value)) in pairs.enumerate() {
+                    if i != 0 {
+                        dest.write_char(' ')?;
+                    }
+                    self.write_text(dest, key.as_str())?;
+                    dest.write_char('=')?;
+                    self.write_text(dest, &value)?;
+                }
+            }
+            KvStyle::Lines => {
+                for (key, value) in pairs {
+                    dest.write_char('\n')?;
+                    self.write_text(dest, key.as_str())?;
+                    dest.write_str(": ")?;
+                    self.write_text(dest, &value)?;
+                }
+            }
+            KvStyle::Json => {
+                // Written manually to keep the order of the pairs.
+                let mut object = String::new();
+                for (i, (key, value)) in pairs.enumerate() {
+                    object.push(if i == 0 { '{' } else { ',' });
+                    object.push_str(&json::Value::from(key.as_str()).to_string());
+                    object.push(':');
+                    object.push_str(&json::Value::String(value).to_string());
+                }
+                if !object.is_empty() {
+                    object.push('}');
+                    dest.write_char(' ')?;
+                    self.write_text(dest, &object)?;
+                }
+            }
+            KvStyle::Hidden => {}
+        }
+        Ok(())
+    }
+}
+
+impl Formatter for DefaultFormatter {
+    fn format(
+        &self,
+        record: &Record,
+        dest: &mut StringBuf,
+        _ctx: &mut FormatterContext,
+    ) -> spdlog::Result<()> {
+        (|| {
+            if self.payload_only {
+                return self.write_text(dest, record.payload());
+            }
+            if self.logger_name
+                && let Some(name) = record.logger_name()
+            {
+                self.write_text(dest, &format!("[{name}] "))?;
+            }
+            if let Some(tag) = &self.log_tag {
+                self.write_text(dest, &format!("#{tag} "))?;
+            }
+            let level = record.level();
+            if self.html_level_badge {
+                write!(
+                    dest,
+                    "<b>{} {}</b>",
+                    level_color(level),
+                    level.as_str().to_ascii_uppercase()
+                )?;
+            } else {
+                dest.write_char('#')?;
+                dest.write_str(level.as_str())?;
+            }
+            dest.write_char(' ')?;
+            self.write_text(dest, record.payload())?;
+            self.write_kv(record, dest)?;
+            if self.source
+                && let Some(loc) = record.source_location()
+            {
+                self.write_text(dest, &format!("\n@{}:{}", loc.file(), loc.line()))?;
+            }
+            Ok(())
+        })()
+        .map_err(spdlog::Error::FormatRecord)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use spdlog::{Logger, error, info};
+
+    use super::*;
+
+    // Formats the logs of `log` with the formatter.
+    fn format(formatter: &DefaultFormatter, log: impl FnOnce(&Logger)) -> Vec<String> {
+        crate::tests::records(log)
+            .iter()
+            .map(|record| {
+                let mut dest = StringBuf::new();
+                formatter
+                    .format(&record.as_ref(), &mut dest, &mut FormatterContext::new())
+                    .unwrap();
+                dest
+            })
+            .collect()
+    }
+
+    #[test]
+    fn pattern() {
+        assert_eq!(
+            DefaultFormatter::new(true).pattern(),
+            "#log #{level} {payload} {kv}\n@{source}"
+        );
+        assert_eq!(
+            DefaultFormatter::new(false).pattern(),
+            "#log #{level} {payload} {kv}"
+        );
+
+        let formatter = DefaultFormatter {
+            logger_name: true,
+            log_tag: Some("my{app}".into()),
+            ..DefaultFormatter::new(false)
+        };
+        assert_eq!(
+            formatter.pattern(),
+            "[{logger}] #my{{app}} #{level} {payload} {kv}"
+        );
+        let formatter = DefaultFormatter {
+            log_tag: None,
+            ..DefaultFormatter::new(true)
+        };
+        assert_eq!(formatter.pattern(), "#{level} {payload} {kv}\n@{source}");
+        let formatter = DefaultFormatter {
+            payload_only: true,
+            ..DefaultFormatter::new(true)
+        };
+        assert_eq!(formatter.pattern(), "{payload}");
+    }
+
+    #[test]
+    fn max_kv_value_len() {
+        let body = r#"{"user":"alice","items":[1,2,3,4,5,6,7,8,9]}"#;
+        let log = |logger: &Logger| info!(logger: logger, "Hello Telegram!", kv: { k = "v", body });
+
+        assert_eq!(
+            format(&DefaultFormatter::new(false), log),
+            [format!("#log #info Hello Telegram! k=v body={body}")]
+        );
+        let formatter = DefaultFormatter {
+            max_kv_value_len: Some(10),
+            ..DefaultFormatter::new(false)
+        };
+        assert_eq!(
+            format(&formatter, log),
+            [r#"#log #info Hello Telegram! k=v body={"user":"…"#]
+        );
+    }
+
+    #[test]
+    fn include_logger_name() {
+        let formatter = DefaultFormatter {
+            logger_name: true,
+            ..DefaultFormatter::new(false)
+        };
+        assert_eq!(
+            format(
+                &formatter,
+                |logger| info!(logger: logger, "Hello Telegram!")
+            ),
+            ["[app] #log #info Hello Telegram! "]
+        );
+    }
+
+    #[test]
+    fn html_level_badge() {
+        let formatter = DefaultFormatter {
+            html_level_badge: true,
+            ..DefaultFormatter::new(false)
+        };
+        assert_eq!(
+            format(&formatter, |logger| {
+                error!(logger: logger, "a < b && c", kv: { k = "<v>" });
+            }),
+            ["#log <b>🔴 ERROR</b> a &lt; b &amp;&amp; c k=&lt;v&gt;"]
+        );
+    }
+
+    #[test]
+    fn kv_style() {
+        for (style, expected, expected_no_kv) in [
+            (
+                KvStyle::Inline,
+                "#log #info deployed b=2 a=\"x\" y c=longe…",
+                "#log #info deployed ",
+            ),
+            (
+                KvStyle::Lines,
+                "#log #info deployed\nb: 2\na: \"x\" y\nc: longe…",
+                "#log #info deployed",
+            ),
+            (
+                KvStyle::Json,
+                r#"#log #info deployed {"b":"2","a":"\"x\" y","c":"longe…"}"#,
+                "#log #info deployed",
+            ),
+            (
+                KvStyle::Hidden,
+                "#log #info deployed",
+                "#log #info deployed",
+            ),
+        ] {
+            let formatter = DefaultFormatter {
+                max_kv_value_len: Some(6),
+                kv_style: style,
+                ..DefaultFormatter::new(false)
+            };
+            let formatted = format(&formatter, |logger| {
+                info!(logger: logger, "deployed", kv: { b = 2, a = "\"x\" y", c = "longest" });
+                info!(logger: logger, "deployed");
+            });
+            assert_eq!(formatted, [expected, expected_no_kv], "{style:?}");
+        }
+    }
+}
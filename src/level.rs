@@ -0,0 +1,26 @@
+use spdlog::Level;
+
+/// Gets a colored emoji representing the severity of a level.
+///
+/// This is the canonical mapping used by this crate, it can also be reused to
+/// build custom formatters.
+///
+/// | Level      | Color |
+/// |------------|-------|
+/// | `Critical` | 🟣    |
+/// | `Error`    | 🔴    |
+/// | `Warn`     | 🟠    |
+/// | `Info`     | 🟢    |
+/// | `Debug`    | 🔵    |
+/// | `Trace`    | ⚪    |
+#[must_use]
+pub fn level_color(level: Level) -> &'static str {
+    match level {
+        Level::Critical => "🟣",
+        Level::Error => "🔴",
+        Level::Warn => "🟠",
+        Level::Info => "🟢",
+        Level::Debug => "🔵",
+        Level::Trace => "⚪",
+    }
+}
@@ -12,49 +12,238 @@
 
 #![warn(missing_docs)]
 
+mod auto_delete;
+mod button;
+mod daily_topic;
 mod error;
+mod formatter;
+mod level;
+mod markup;
+mod policy;
+mod quote;
 mod recipient;
+mod reply_thread;
+mod report;
 mod request;
+mod source_tag;
+mod stats;
+mod text;
+mod topic;
+mod transport;
 
-use std::{convert::Infallible, sync::atomic::Ordering};
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    io::{self, Write},
+    net::IpAddr,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread,
+    time::{Duration, Instant},
+};
 
-use atomic::Atomic;
-pub use error::{Error, Result};
+use arc_swap::ArcSwapOption;
+use auto_delete::AutoDeleter;
+pub use button::Button;
+use daily_topic::DailyTopic;
+pub use error::{Error, ReqwestDesensitizedError, Result};
+use formatter::DefaultFormatter;
+pub use formatter::KvStyle;
+pub use level::level_color;
+pub use markup::{HtmlEscaped, ParseMode, escape_html};
+pub use policy::{BlockedPolicy, EmptyPolicy, NotifyPolicy};
+pub use quote::Quote;
 pub use recipient::Recipient;
-use request::Requester;
+use reply_thread::ReplyThreads;
+pub use report::BuildReport;
+use request::{BeforeSendFn, LatencyFn, Message, Requester};
+use serde_json as json;
+pub use source_tag::SourceTag;
 use spdlog::{
-    ErrorHandler, Record, StringBuf,
-    formatter::{Formatter, FormatterContext, PatternFormatter, pattern},
+    AtomicLevelFilter, ErrorHandler, Record, StringBuf,
+    formatter::{Formatter, FormatterContext},
     prelude::*,
     sink::{GetSinkProp, Sink, SinkProp},
 };
+use stats::SendCounters;
+pub use stats::SendStats;
+use text::MAX_TEXT_LEN;
+pub use topic::TopicIconColor;
+pub use transport::Transport;
+use transport::{ClientOptions, HttpTransport};
 use url::Url;
 
 /// A sink with a Telegram recipient as the target via Telegram Bot API.
 ///
 /// This sink involves network operations. If you don't want it to block the
-/// thread, you may want to use it in combination with [`AsyncPoolSink`].
+/// thread, you may want to use it in combination with [`AsyncPoolSink`], see
+/// [`TelegramSink::into_async_pool`] (requires crate feature `multi-thread`).
 ///
 /// [`AsyncPoolSink`]: https://docs.rs/spdlog-rs/0.5.1/spdlog/sink/struct.AsyncPoolSink.html
 pub struct TelegramSink {
-    prop: SinkProp,
-    silence: Atomic<LevelFilter>,
-    requester: Requester,
+    // Shared with the auto-delete thread to report errors
+    prop: Arc<SinkProp>,
+    silence: AtomicLevelFilter,
+    silence_fn: ArcSwapOption<SilenceFn>,
+    force_notify: LevelFilter,
+    // The window and the instant it started at, i.e. the last audible message
+    notify_rate_limit: Option<(Duration, Mutex<Option<Instant>>)>,
+    header: Option<String>,
+    // Rendered once from the source tag
+    footer_fields: Vec<String>,
+    // The start instant for the uptime, if enabled
+    started: Option<Instant>,
+    ellipsis: String,
+    on_empty: EmptyPolicy,
+    parse_mode: Option<ParseMode>,
+    parse_mode_key: Option<String>,
+    code_block_multiline: bool,
+    collapse_long: Option<usize>,
+    trim_output: bool,
+    retry_predicate: Option<RetryPredicate>,
+    forward: Option<Forward>,
+    reply_to_if: Option<(LevelFilter, u64)>,
+    inline_buttons: Option<ButtonsFn>,
+    raw_payload: bool,
+    source_as_reply: bool,
+    thread_id_key: Option<String>,
+    thread_by_key: Option<String>,
+    on_blocked: BlockedPolicy,
+    category_icons: Option<CategoryIcons>,
+    // Set if the bot is blocked and `BlockedPolicy::Disable` is configured
+    blocked: AtomicBool,
+    // Unset until a recipient is bound, if built with `build_deferred`
+    bound: AtomicBool,
+    // The pattern of the default formatter, if used
+    default_pattern: Option<String>,
+    // The `sendMessage` endpoint of the default transport
+    endpoint_redacted: Option<String>,
+    // `None` if no Bot API call was made while building
+    token_validated: Option<bool>,
+    // Created again for every newly bound recipient
+    create_topic: Option<(String, Option<TopicIconColor>)>,
+    daily_topic: Option<DailyTopic>,
+    requester: Arc<Requester>,
+    auto_delete: Option<AutoDeleter>,
+    counters: SendCounters,
+}
+
+struct CategoryIcons {
+    key: String,
+    icons: HashMap<String, String>,
 }
 
+struct Forward {
+    level_filter: LevelFilter,
+    from_chat_id: i64,
+    message_id: u64,
+}
+
+type RetryPredicate = Box<dyn Fn(&Error, u32) -> Option<Duration> + Send + Sync>;
+
+type ButtonsFn = Box<dyn Fn(&Record) -> Option<Vec<Vec<Button>>> + Send + Sync>;
+
+type SilenceFn = Arc<dyn Fn(&Record) -> bool + Send + Sync>;
+
 impl TelegramSink {
+    /// Constructs a `TelegramSink` with default parameters, sending logs to
+    /// the recipient with the bot token.
+    ///
+    /// This is a shortcut for the common case, equivalent to
+    /// `TelegramSink::builder().bot_token(bot_token).recipient(recipient).
+    /// build()`. Use [`TelegramSink::builder`] to customize other
+    /// parameters.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    ///
+    /// use spdlog::prelude::*;
+    /// use spdlog_telegram::TelegramSink;
+    ///
+    /// let sink = TelegramSink::new("1234567890:AbCdEfGhiJkLmNoPq1R2s3T4u5V6w7X8y9z", "@my_channel")?;
+    /// let logger = Logger::builder().sink(Arc::new(sink)).build()?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn new<T, R>(bot_token: T, recipient: R) -> Result<Self>
+    where
+        T: Into<String>,
+        R: Into<Recipient>,
+    {
+        Self::builder()
+            .bot_token(bot_token)
+            .recipient(recipient)
+            .build()
+    }
+
     /// Gets a builder of `TelegramSink` with default parameters:
     ///
-    /// | Parameter         | Default Value                                                                           |
-    /// |-------------------|-----------------------------------------------------------------------------------------|
-    /// | [level_filter]    | `All`                                                                                   |
-    /// | [formatter]       | pattern `"#log #{level} {payload} {kv}\n@{source}"` or `"#log #{level} {payload} {kv}"` |
-    /// | [error_handler]   | [`ErrorHandler::default()`]                                                             |
-    /// |                   |                                                                                         |
-    /// | [server_url]      | `"https://api.telegram.org"`                                                            |
-    /// | [bot_token]       | *must be specified*                                                                     |
-    /// | [recipient]       | *must be specified*                                                                     |
-    /// | [silence]         | `Off`                                                                                   |
+    /// | Parameter                     | Default Value                                                                           |
+    /// |-------------------------------|-----------------------------------------------------------------------------------------|
+    /// | [level_filter]                | `All`                                                                                   |
+    /// | [formatter]                   | pattern `"#log #{level} {payload} {kv}\n@{source}"` or `"#log #{level} {payload} {kv}"` |
+    /// | [error_handler]               | [`ErrorHandler::default()`]                                                             |
+    /// |                               |                                                                                         |
+    /// | [server_url]                  | `"https://api.telegram.org"`                                                            |
+    /// | [bot_token]                   | *must be specified*                                                                     |
+    /// | [recipient]                   | *must be specified*                                                                     |
+    /// | [silence]                     | `Off`                                                                                   |
+    /// | [always_silent]               | *sets* [silence]                                                                        |
+    /// | [always_audible]              | *sets* [silence]                                                                        |
+    /// | [source_tag]                  | *none*                                                                                  |
+    /// | [http_client]                 | *a new client*                                                                          |
+    /// | [content_type]                | `"application/json"`                                                                    |
+    /// | [create_topic]                | *none*                                                                                  |
+    /// | [max_kv_value_len]            | *none*                                                                                  |
+    /// | [on_empty]                    | [`EmptyPolicy::Skip`]                                                                   |
+    /// | [log_tag]                     | `Some("log")`                                                                           |
+    /// | [include_logger_name]         | `false`                                                                                 |
+    /// | [parse_mode]                  | *plain text*                                                                            |
+    /// | [code_block_multiline]        | `false`                                                                                 |
+    /// | [dry_run]                     | `false`                                                                                 |
+    /// | [retry_predicate]             | *no retry*                                                                              |
+    /// | [forward_on]                  | *none*                                                                                  |
+    /// | [raw_payload]                 | `false`                                                                                 |
+    /// | [follow_chat_migration]       | `false`                                                                                 |
+    /// | [transport]                   | *HTTP*                                                                                  |
+    /// | [auto_delete]                 | *never*                                                                                 |
+    /// | [thread_id_key]               | *none*                                                                                  |
+    /// | [http2_prior_knowledge]       | `false`                                                                                 |
+    /// | [on_blocked]                  | [`BlockedPolicy::Error`]                                                                |
+    /// | [category_icons]              | *none*                                                                                  |
+    /// | [allow_paid_broadcast]        | `false`                                                                                 |
+    /// | [link_preview]                | *disabled for plain text*                                                               |
+    /// | [environment_badge]           | *none*                                                                                  |
+    /// | [payload_template]            | *none*                                                                                  |
+    /// | [force_notify]                | `Off`                                                                                   |
+    /// | [reply_to_if]                 | *none*                                                                                  |
+    /// | [include_uptime]              | `false`                                                                                 |
+    /// | [ellipsis]                    | `"…"`                                                                                   |
+    /// | [html_level_badge]            | `false`                                                                                 |
+    /// | [tcp_nodelay]                 | *reqwest's default*                                                                     |
+    /// | [local_address]               | *none*                                                                                  |
+    /// | [notify_rate_limit]           | *none*                                                                                  |
+    /// | [collapse_long]               | *never*                                                                                 |
+    /// | [recipient_for]               | *none*                                                                                  |
+    /// | [parse_mode_key]              | *none*                                                                                  |
+    /// | [inline_buttons]              | *none*                                                                                  |
+    /// | [kv_style]                    | [`KvStyle::Inline`]                                                                     |
+    /// | [allow_sending_without_reply] | `false`                                                                                 |
+    /// | [legacy_link_preview]         | `false`                                                                                 |
+    /// | [ok_field]                    | `"ok"`                                                                                  |
+    /// | [description_field]           | `"description"`                                                                         |
+    /// | [source_as_reply]             | `false`                                                                                 |
+    /// | [thread_by_key]               | *none*                                                                                  |
+    /// | [max_request_bytes]           | `52428800` (50 MiB)                                                                     |
+    /// | [require_topic]               | `false`                                                                                 |
+    /// | [daily_topic]                 | *none*                                                                                  |
+    /// | [desensitize_errors]          | `true`                                                                                  |
+    /// | [trim_output]                 | `true`                                                                                  |
+    /// | [before_send]                 | *none*                                                                                  |
+    /// | [on_latency]                  | *none*                                                                                  |
     ///
     /// [level_filter]: TelegramSinkBuilder::level_filter
     /// [formatter]: TelegramSinkBuilder::formatter
@@ -64,31 +253,189 @@ impl TelegramSink {
     /// [bot_token]: TelegramSinkBuilder::bot_token
     /// [recipient]: TelegramSinkBuilder::recipient
     /// [silence]: TelegramSinkBuilder::silence
+    /// [always_silent]: TelegramSinkBuilder::always_silent
+    /// [always_audible]: TelegramSinkBuilder::always_audible
+    /// [source_tag]: TelegramSinkBuilder::source_tag
+    /// [http_client]: TelegramSinkBuilder::http_client
+    /// [content_type]: TelegramSinkBuilder::content_type
+    /// [create_topic]: TelegramSinkBuilder::create_topic
+    /// [max_kv_value_len]: TelegramSinkBuilder::max_kv_value_len
+    /// [on_empty]: TelegramSinkBuilder::on_empty
+    /// [log_tag]: TelegramSinkBuilder::log_tag
+    /// [include_logger_name]: TelegramSinkBuilder::include_logger_name
+    /// [parse_mode]: TelegramSinkBuilder::parse_mode
+    /// [code_block_multiline]: TelegramSinkBuilder::code_block_multiline
+    /// [dry_run]: TelegramSinkBuilder::dry_run
+    /// [retry_predicate]: TelegramSinkBuilder::retry_predicate
+    /// [forward_on]: TelegramSinkBuilder::forward_on
+    /// [raw_payload]: TelegramSinkBuilder::raw_payload
+    /// [follow_chat_migration]: TelegramSinkBuilder::follow_chat_migration
+    /// [transport]: TelegramSinkBuilder::transport
+    /// [auto_delete]: TelegramSinkBuilder::auto_delete
+    /// [thread_id_key]: TelegramSinkBuilder::thread_id_key
+    /// [http2_prior_knowledge]: TelegramSinkBuilder::http2_prior_knowledge
+    /// [on_blocked]: TelegramSinkBuilder::on_blocked
+    /// [category_icons]: TelegramSinkBuilder::category_icons
+    /// [allow_paid_broadcast]: TelegramSinkBuilder::allow_paid_broadcast
+    /// [link_preview]: TelegramSinkBuilder::link_preview
+    /// [environment_badge]: TelegramSinkBuilder::environment_badge
+    /// [payload_template]: TelegramSinkBuilder::payload_template
+    /// [force_notify]: TelegramSinkBuilder::force_notify
+    /// [reply_to_if]: TelegramSinkBuilder::reply_to_if
+    /// [include_uptime]: TelegramSinkBuilder::include_uptime
+    /// [ellipsis]: TelegramSinkBuilder::ellipsis
+    /// [html_level_badge]: TelegramSinkBuilder::html_level_badge
+    /// [tcp_nodelay]: TelegramSinkBuilder::tcp_nodelay
+    /// [local_address]: TelegramSinkBuilder::local_address
+    /// [notify_rate_limit]: TelegramSinkBuilder::notify_rate_limit
+    /// [collapse_long]: TelegramSinkBuilder::collapse_long
+    /// [recipient_for]: TelegramSinkBuilder::recipient_for
+    /// [parse_mode_key]: TelegramSinkBuilder::parse_mode_key
+    /// [inline_buttons]: TelegramSinkBuilder::inline_buttons
+    /// [kv_style]: TelegramSinkBuilder::kv_style
+    /// [allow_sending_without_reply]: TelegramSinkBuilder::allow_sending_without_reply
+    /// [legacy_link_preview]: TelegramSinkBuilder::legacy_link_preview
+    /// [ok_field]: TelegramSinkBuilder::ok_field
+    /// [description_field]: TelegramSinkBuilder::description_field
+    /// [source_as_reply]: TelegramSinkBuilder::source_as_reply
+    /// [thread_by_key]: TelegramSinkBuilder::thread_by_key
+    /// [max_request_bytes]: TelegramSinkBuilder::max_request_bytes
+    /// [require_topic]: TelegramSinkBuilder::require_topic
+    /// [daily_topic]: TelegramSinkBuilder::daily_topic
+    /// [desensitize_errors]: TelegramSinkBuilder::desensitize_errors
+    /// [trim_output]: TelegramSinkBuilder::trim_output
+    /// [before_send]: TelegramSinkBuilder::before_send
+    /// [on_latency]: TelegramSinkBuilder::on_latency
     #[must_use]
     pub fn builder() -> TelegramSinkBuilder<(), ()> {
-        let prop = SinkProp::default();
-        if spdlog::source_location_current!().is_some() {
-            prop.set_formatter(PatternFormatter::new(pattern!(
-                "#log #{level} {payload} {kv}\n@{source}"
-            )));
-        } else {
-            prop.set_formatter(PatternFormatter::new(pattern!(
-                "#log #{level} {payload} {kv}"
-            )))
-        };
+        Self::builder_with_source(spdlog::source_location_current!().is_some())
+    }
+
+    /// Gets a builder of `TelegramSink` with default parameters, specifying
+    /// explicitly whether the default formatter includes the source location.
+    ///
+    /// [`TelegramSink::builder`] includes the source location only if it's
+    /// available, i.e. crate feature `source-location` of spdlog-rs is enabled,
+    /// so the default formatter depends on how the dependency graph is
+    /// compiled. Use this instead to make the choice independent of it.
+    ///
+    /// If enabled, the source location is appended as `"\n@{source}"`. It's
+    /// omitted for records without a source location.
+    #[must_use]
+    pub fn builder_with_source(source: bool) -> TelegramSinkBuilder<(), ()> {
         TelegramSinkBuilder {
-            prop,
-            server_url: None,
             bot_token: (),
             recipient: (),
-            silence: LevelFilter::Off,
+            options: BuilderOptions {
+                prop: SinkProp::default(),
+                default_formatter: Some(DefaultFormatter::new(source)),
+                server_url: None,
+                silence: LevelFilter::Off,
+                source_tag: None,
+                http_client: None,
+                content_type: Some("application/json".into()),
+                create_topic: None,
+                on_empty: EmptyPolicy::default(),
+                parse_mode: None,
+                code_block_multiline: false,
+                dry_run: None,
+                retry_predicate: None,
+                forward: None,
+                raw_payload: false,
+                follow_chat_migration: false,
+                transport: None,
+                auto_delete: None,
+                thread_id_key: None,
+                http2_prior_knowledge: false,
+                on_blocked: BlockedPolicy::default(),
+                category_icons: None,
+                allow_paid_broadcast: false,
+                link_preview: None,
+                environment_badge: None,
+                payload_template: None,
+                force_notify: LevelFilter::Off,
+                reply_to_if: None,
+                include_uptime: false,
+                ellipsis: text::ELLIPSIS.into(),
+                tcp_nodelay: None,
+                local_address: None,
+                notify_rate_limit: None,
+                collapse_long: None,
+                recipient_rules: vec![],
+                parse_mode_key: None,
+                inline_buttons: None,
+                allow_sending_without_reply: false,
+                legacy_link_preview: false,
+                ok_field: "ok".into(),
+                description_field: "description".into(),
+                source_as_reply: false,
+                thread_by_key: None,
+                max_request_bytes: request::DEFAULT_MAX_REQUEST_BYTES,
+                require_topic: false,
+                daily_topic: None,
+                desensitize_errors: true,
+                trim_output: true,
+                before_send: None,
+                on_latency: None,
+            },
         }
     }
 
+    /// Wraps the sink in an [`AsyncPoolSink`] with default parameters.
+    ///
+    /// Logs are then sent to Telegram in a background thread pool, so that
+    /// logging threads are not blocked on network I/O. Use
+    /// [`AsyncPoolSink::builder`] directly if you need to customize the pool.
+    ///
+    /// ## Examples
+    ///
+    /// ```no_run
+    /// use std::sync::Arc;
+    ///
+    /// use spdlog::prelude::*;
+    /// use spdlog_telegram::TelegramSink;
+    ///
+    /// let sink = TelegramSink::builder()
+    ///     .bot_token("1234567890:AbCdEfGhiJkLmNoPq1R2s3T4u5V6w7X8y9z")
+    ///     .recipient("@my_channel")
+    ///     .build()?
+    ///     .into_async_pool()?;
+    /// let logger = Logger::builder().sink(Arc::new(sink)).build()?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// [`AsyncPoolSink`]: spdlog::sink::AsyncPoolSink
+    /// [`AsyncPoolSink::builder`]: spdlog::sink::AsyncPoolSink::builder
+    #[cfg(feature = "multi-thread")]
+    pub fn into_async_pool(self) -> spdlog::Result<spdlog::sink::AsyncPoolSink> {
+        spdlog::sink::AsyncPoolSink::builder()
+            .sink(Arc::new(self))
+            .build()
+    }
+
+    /// Gets the level filter of logs to send.
+    ///
+    /// This is the same as [`SinkPropAccess::level_filter`], available without
+    /// importing the trait.
+    ///
+    /// [`SinkPropAccess::level_filter`]: spdlog::sink::SinkPropAccess::level_filter
+    #[must_use]
+    pub fn level_filter(&self) -> LevelFilter {
+        self.prop.level_filter()
+    }
+
+    /// Sets the level filter of logs to send.
+    ///
+    /// It takes effect immediately for subsequent logs, the same way as
+    /// [`TelegramSink::set_silence`] for the notification sound.
+    pub fn set_level_filter(&self, level_filter: LevelFilter) {
+        self.prop.set_level_filter(level_filter);
+    }
+
     /// Gets the silence level filter.
     #[must_use]
     pub fn silence(&self) -> LevelFilter {
-        self.silence.load(Ordering::Relaxed)
+        self.silence.get()
     }
 
     /// Sets the silence level filter.
@@ -96,7 +443,473 @@ impl TelegramSink {
     /// Logs with level matching the filter will be sent with
     /// `disable_notification` set to `true`.
     pub fn set_silence(&self, silent_if: LevelFilter) {
-        self.silence.store(silent_if, Ordering::Relaxed);
+        self.silence.set(silent_if);
+    }
+
+    /// Sets all logs to be sent without notification sound.
+    ///
+    /// This is equivalent to `set_silence(LevelFilter::All)`.
+    pub fn set_always_silent(&self) {
+        self.set_silence(LevelFilter::All);
+    }
+
+    /// Sets all logs to be sent with notification sound.
+    ///
+    /// This is equivalent to `set_silence(LevelFilter::Off)`.
+    pub fn set_always_audible(&self) {
+        self.set_silence(LevelFilter::Off);
+    }
+
+    /// Sets a silence predicate.
+    ///
+    /// Logs for which the predicate returns `true` will be sent with
+    /// `disable_notification` set to `true`. When set, it takes precedence over
+    /// the silence level filter, but not over the [force-notify filter].
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use std::sync::{
+    ///     Arc,
+    ///     atomic::{AtomicBool, Ordering},
+    /// };
+    ///
+    /// # fn make_sink() -> spdlog_telegram::TelegramSink {
+    /// #     spdlog_telegram::TelegramSink::builder()
+    /// #         .bot_token("token")
+    /// #         .recipient(-1001234567890)
+    /// #         .build()
+    /// #         .unwrap()
+    /// # }
+    /// static QUIET_HOURS: AtomicBool = AtomicBool::new(false);
+    ///
+    /// let sink = make_sink();
+    /// sink.set_silence_fn(Arc::new(|_record| QUIET_HOURS.load(Ordering::Relaxed)));
+    /// ```
+    ///
+    /// [force-notify filter]: TelegramSinkBuilder::force_notify
+    pub fn set_silence_fn(&self, silent_if: Arc<dyn Fn(&Record) -> bool + Send + Sync>) {
+        self.silence_fn.store(Some(Arc::new(silent_if)));
+    }
+
+    /// Removes the silence predicate set by [`TelegramSink::set_silence_fn`],
+    /// falling back to the silence level filter.
+    pub fn clear_silence_fn(&self) {
+        self.silence_fn.store(None);
+    }
+
+    /// Establishes a connection to the Bot API server ahead of the first log.
+    ///
+    /// Connecting involves a handshake that adds noticeable latency to the
+    /// first request, e.g. TLS to `api.telegram.org`. Call this at startup
+    /// for latency-sensitive alerting, so that the first log goes out on a
+    /// pooled connection. It sends a cheap [`getMe`] request, which also
+    /// validates the bot token.
+    ///
+    /// This is optional and idempotent, it can be called again at any time,
+    /// e.g. after a long idle period closed the pooled connection.
+    ///
+    /// [`getMe`]: https://core.telegram.org/bots/api#getme
+    pub fn prewarm(&self) -> Result<()> {
+        self.requester.get_me()
+    }
+
+    /// Sets the recipient of logs.
+    ///
+    /// Subsequent logs are sent to the new recipient. This also binds the
+    /// recipient of a sink built with [`TelegramSinkBuilder::build_deferred`].
+    ///
+    /// The recipient is prepared the same way as when building the sink, i.e.
+    /// the [linked discussion group] is looked up and the [forum topic] is
+    /// created in the new chat if configured, which involves network
+    /// operations. If it fails, the previous recipient is kept.
+    ///
+    /// [linked discussion group]: Recipient::linked_discussion
+    /// [forum topic]: TelegramSinkBuilder::create_topic
+    pub fn set_recipient<R>(&self, recipient: R) -> Result<()>
+    where
+        R: Into<Recipient>,
+    {
+        self.requester
+            .bind(recipient.into(), self.create_topic.as_ref())?;
+        self.bound.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Gets a snapshot of the send counters since the sink was built or the
+    /// last [`TelegramSink::reset_stats`] call.
+    #[must_use]
+    pub fn stats(&self) -> SendStats {
+        self.counters.snapshot()
+    }
+
+    /// Gets the pattern the default formatter is equivalent to, or `None` if a
+    /// custom [formatter] or [prop] was specified when building the sink.
+    ///
+    /// It reflects the choice between the [default patterns], i.e. whether the
+    /// source location is included, as well as the options of the default
+    /// formatter such as the [log tag]. The [HTML level badge] is not
+    /// reflected. Formatters set on the sink after building are not reflected
+    /// either.
+    ///
+    /// [formatter]: TelegramSinkBuilder::formatter
+    /// [prop]: TelegramSinkBuilder::prop
+    /// [default patterns]: TelegramSink::builder
+    /// [log tag]: TelegramSinkBuilder::log_tag
+    /// [HTML level badge]: TelegramSinkBuilder::html_level_badge
+    #[must_use]
+    pub fn default_pattern(&self) -> Option<&str> {
+        self.default_pattern.as_deref()
+    }
+
+    /// Gets the `sendMessage` endpoint the sink sends logs to, with the bot
+    /// token replaced by `<token>`.
+    ///
+    /// Useful for diagnosing issues with the [server URL], e.g. a local Bot API
+    /// server responding 404, without leaking the token, e.g.
+    /// `"https://api.telegram.org/bot<token>/sendMessage"`.
+    ///
+    /// Returns `None` if a custom [transport] is used.
+    ///
+    /// [server URL]: TelegramSinkBuilder::server_url
+    /// [transport]: TelegramSinkBuilder::transport
+    #[must_use]
+    pub fn endpoint_redacted(&self) -> Option<&str> {
+        self.endpoint_redacted.as_deref()
+    }
+
+    /// Gets whether the bot token was validated while building the sink.
+    ///
+    /// The token is validated by a successful Bot API call, i.e. the test
+    /// message of [`TelegramSinkBuilder::build_and_test`], resolving the
+    /// [linked discussion group] or creating the [forum topic]. Building fails
+    /// if the call fails, so an invalid token never ends up in a sink.
+    ///
+    /// Returns `None` if no such call was requested, e.g. for
+    /// [`TelegramSinkBuilder::build`] with a plain chat ID, and `Some(false)`
+    /// if it was only written in [dry-run mode]. This lets health checks tell
+    /// "not checked" from "checked and good".
+    ///
+    /// [linked discussion group]: Recipient::linked_discussion
+    /// [forum topic]: TelegramSinkBuilder::create_topic
+    /// [dry-run mode]: TelegramSinkBuilder::dry_run
+    #[must_use]
+    pub fn token_validated(&self) -> Option<bool> {
+        self.token_validated
+    }
+
+    /// Gets the pattern of the default formatter with default options, as
+    /// chosen by [`TelegramSink::builder_with_source`].
+    ///
+    /// [`TelegramSink::builder`] passes whether the source location is
+    /// available, see [`TelegramSink::default_pattern`] for the pattern a
+    /// built sink ended up with.
+    #[must_use]
+    pub fn default_pattern_for(source: bool) -> String {
+        DefaultFormatter::new(source).pattern()
+    }
+
+    /// Resets the send counters to zero.
+    pub fn reset_stats(&self) {
+        self.counters.reset();
+    }
+
+    /// Renders a record into the message text, exactly as the sink would send
+    /// it, but without sending it.
+    ///
+    /// This runs the full pipeline, i.e. the formatter, icons, code blocks,
+    /// the source tag and truncation. Useful for testing the presentation
+    /// configuration without a Bot API server.
+    ///
+    /// Returns `None` if the log would be skipped, e.g. by the level filter or
+    /// [`EmptyPolicy::Skip`].
+    pub fn render(&self, record: &Record) -> spdlog::Result<Option<String>> {
+        if !self.should_log(record.level()) {
+            return Ok(None);
+        }
+        Ok(self.render_text(record)?.map(|(text, _)| text))
+    }
+
+    // Truncates the text to the length limit, making room for the header, the icon
+    // and the footer, so that they never push a message over the limit.
+    fn reserve_decorations(
+        &self,
+        text: &mut String,
+        parse_mode: Option<ParseMode>,
+        icon: Option<&str>,
+        footer: Option<&str>,
+    ) {
+        let reserved = [self.header.as_deref(), icon, footer]
+            .into_iter()
+            .flatten()
+            .map(|decoration| text::utf16_len(decoration) + 1)
+            .sum::<usize>();
+        markup::truncate(
+            text,
+            MAX_TEXT_LEN.saturating_sub(reserved),
+            &self.ellipsis,
+            parse_mode,
+        );
+    }
+
+    fn category_icon(&self, record: &Record) -> Option<&str> {
+        let icons = self.category_icons.as_ref()?;
+        record
+            .key_values()
+            .into_iter()
+            .find(|(key, _)| key.as_str() == icons.key)
+            .and_then(|(_, value)| icons.icons.get(&value.to_string()))
+            .map(String::as_str)
+    }
+
+    // Returns the parse mode overridden by the record, `Some(None)` for plain text.
+    fn record_parse_mode(&self, record: &Record) -> Option<Option<ParseMode>> {
+        let key = self.parse_mode_key.as_ref()?;
+        let (_, value) = record
+            .key_values()
+            .into_iter()
+            .find(|(k, _)| k.as_str() == key)?;
+        match value.to_string().to_ascii_lowercase().as_str() {
+            "html" => Some(Some(ParseMode::Html)),
+            "markdownv2" => Some(Some(ParseMode::MarkdownV2)),
+            "none" => Some(None),
+            _ => None,
+        }
+    }
+
+    // The uptime changes on every send, so the footer is rendered per message.
+    fn footer(&self) -> Option<String> {
+        let uptime = self
+            .started
+            .map(|started| format!("uptime={}", source_tag::format_uptime(started.elapsed())));
+        source_tag::render_fields(
+            self.footer_fields
+                .iter()
+                .map(String::as_str)
+                .chain(uptime.as_deref()),
+        )
+    }
+
+    fn send_text(&self, record: &Record, disable_notification: bool) -> spdlog::Result<()> {
+        let Some((text, parse_mode)) = self.render_text(record)? else {
+            return Ok(());
+        };
+        let thread_value = self.thread_by_key.as_ref().and_then(|key| {
+            record
+                .key_values()
+                .into_iter()
+                .find(|(k, _)| k.as_str() == key)
+                .map(|(_, value)| value.to_string())
+        });
+        let thread_root = thread_value.as_deref().and_then(|value| {
+            self.requester
+                .reply_threads()
+                .and_then(|threads| threads.root(value))
+        });
+        let message = Message {
+            text,
+            disable_notification,
+            parse_mode,
+            thread_id: self
+                .thread_id_key
+                .as_ref()
+                .and_then(|key| {
+                    record
+                        .key_values()
+                        .into_iter()
+                        .find(|(k, _)| k.as_str() == key)
+                        .and_then(|(_, value)| value.to_u64())
+                })
+                .or_else(|| self.daily_thread_id(record.level())),
+            reply_to: thread_root.or_else(|| {
+                self.reply_to_if
+                    .filter(|(level_filter, _)| level_filter.test(record.level()))
+                    .map(|(_, message_id)| message_id)
+            }),
+            buttons: self
+                .inline_buttons
+                .as_ref()
+                .and_then(|inline_buttons| inline_buttons(record))
+                .filter(|rows| !rows.is_empty()),
+            level: Some(record.level()),
+        };
+        let result = self.send_with_retry(&message);
+        // Logs dropped by the `before_send` hook are neither delivered nor failed.
+        if !matches!(result, Ok(None)) {
+            self.counters.record(record.level(), result.is_ok());
+        }
+        match result {
+            Ok(None) => Ok(()),
+            Ok(Some(sent)) => {
+                if let Some(auto_delete) = &self.auto_delete {
+                    auto_delete.schedule(&sent);
+                }
+                if let (None, Some(value), Some(threads), Some(message_id)) = (
+                    thread_root,
+                    thread_value,
+                    self.requester.reply_threads(),
+                    sent.get("message_id").and_then(|j| j.as_u64()),
+                ) {
+                    threads.insert(value, message_id);
+                }
+                self.send_source_reply(record, &message, &sent)
+                    .map_err(|err| spdlog::Error::Downstream(err.into()))
+            }
+            Err(Error::BotBlocked) if self.on_blocked == BlockedPolicy::Ignore => Ok(()),
+            Err(Error::BotBlocked) if self.on_blocked == BlockedPolicy::Disable => {
+                // Reports only once, even if multiple sends fail concurrently.
+                if self.blocked.swap(true, Ordering::Relaxed) {
+                    Ok(())
+                } else {
+                    Err(spdlog::Error::Downstream(Error::BotBlocked.into()))
+                }
+            }
+            Err(err) => Err(spdlog::Error::Downstream(err.into())),
+        }
+    }
+
+    // Returns the text to be sent and its parse mode, or `None` if the log
+    // should be skipped.
+    fn render_text(&self, record: &Record) -> spdlog::Result<Option<(String, Option<ParseMode>)>> {
+        let mut string_buf = StringBuf::new();
+        let mut ctx = FormatterContext::new();
+        self.prop
+            .formatter()
+            .format(record, &mut string_buf, &mut ctx)?;
+
+        let mut text = string_buf;
+        if text.trim().is_empty() {
+            match &self.on_empty {
+                EmptyPolicy::Skip => return Ok(None),
+                EmptyPolicy::Placeholder(placeholder) => text = placeholder.clone(),
+                EmptyPolicy::Error => {
+                    return Err(spdlog::Error::Downstream(Error::EmptyText.into()));
+                }
+            }
+        }
+        let mut parse_mode = self.record_parse_mode(record).unwrap_or(self.parse_mode);
+        if !self.raw_payload {
+            if self.trim_output {
+                // The indentation is meaningful in code blocks, e.g. of backtraces.
+                let keep_indent = self.code_block_multiline && text.contains('\n');
+                text::trim(&mut text, keep_indent);
+            }
+            let icon = self.category_icon(record);
+            let footer = self.footer();
+            self.reserve_decorations(&mut text, parse_mode, icon, footer.as_deref());
+            if self
+                .collapse_long
+                .is_some_and(|threshold| text::utf16_len(&text) > threshold)
+            {
+                (text, parse_mode) = markup::expandable_blockquote(text, parse_mode);
+            } else if self.code_block_multiline && text.contains('\n') {
+                (text, parse_mode) = markup::code_block(text, parse_mode);
+            }
+            if let Some(icon) = icon {
+                text.insert_str(0, &format!("{} ", markup::escape(icon, parse_mode)));
+            }
+            if let Some(header) = &self.header {
+                text.insert_str(0, &format!("{}\n", markup::escape(header, parse_mode)));
+            }
+            if let Some(footer) = footer {
+                text.push('\n');
+                text.push_str(&markup::escape(&footer, parse_mode));
+            }
+        }
+        Ok(Some((text, parse_mode)))
+    }
+
+    // Returns the thread ID of the daily topic, if enabled. If creating the topic
+    // fails, it's reported to the error handler and the log is sent to the thread
+    // of the recipient, e.g. the General topic.
+    fn daily_thread_id(&self, level: Level) -> Option<u64> {
+        let daily_topic = self.daily_topic.as_ref()?;
+        daily_topic
+            .thread_id(&self.requester, Some(level))
+            .unwrap_or_else(|err| {
+                self.prop
+                    .call_error_handler(spdlog::Error::Downstream(err.into()));
+                None
+            })
+    }
+
+    // Sends the source location of the record as a reply to the sent message, if
+    // enabled.
+    fn send_source_reply(
+        &self,
+        record: &Record,
+        message: &Message,
+        sent: &json::Value,
+    ) -> Result<()> {
+        if !self.source_as_reply {
+            return Ok(());
+        }
+        // There is no message ID to reply to in dry-run mode.
+        let (Some(loc), Some(message_id)) = (
+            record.source_location(),
+            sent.get("message_id").and_then(|j| j.as_u64()),
+        ) else {
+            return Ok(());
+        };
+        let reply = Message {
+            text: format!("@{}:{}", loc.file(), loc.line()),
+            disable_notification: true,
+            parse_mode: None,
+            thread_id: message.thread_id,
+            reply_to: Some(message_id),
+            buttons: None,
+            level: message.level,
+        };
+        if let (Some(sent), Some(auto_delete)) = (self.send_with_retry(&reply)?, &self.auto_delete)
+        {
+            auto_delete.schedule(&sent);
+        }
+        Ok(())
+    }
+
+    fn send_with_retry(&self, message: &Message) -> Result<Option<json::Value>> {
+        let mut attempt = 1;
+        loop {
+            let result = self.requester.send_log(message);
+            let Err(err) = &result else {
+                return result;
+            };
+            match self.retry_predicate.as_ref().and_then(|f| f(err, attempt)) {
+                None => return result,
+                Some(delay) => {
+                    self.counters.record_retry();
+                    thread::sleep(delay);
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    // Called for every audible message, returns whether its sound is throttled by
+    // the notify rate limit.
+    fn is_throttled(&self) -> bool {
+        let Some((window, started)) = &self.notify_rate_limit else {
+            return false;
+        };
+        let mut started = started.lock().unwrap_or_else(|err| err.into_inner());
+        let now = Instant::now();
+        match *started {
+            Some(instant) if now.duration_since(instant) < *window => true,
+            _ => {
+                *started = Some(now);
+                false
+            }
+        }
+    }
+
+    fn is_silent(&self, record: &Record) -> bool {
+        if self.force_notify.test(record.level()) {
+            return false;
+        }
+        match &*self.silence_fn.load() {
+            Some(silent_if) => silent_if(record),
+            None => self.silence().test(record.level()),
+        }
     }
 }
 
@@ -108,16 +921,41 @@ impl GetSinkProp for TelegramSink {
 
 impl Sink for TelegramSink {
     fn log(&self, record: &Record) -> spdlog::Result<()> {
-        let mut string_buf = StringBuf::new();
-        let mut ctx = FormatterContext::new();
-        self.prop
-            .formatter()
-            .format(record, &mut string_buf, &mut ctx)?;
+        // `Logger` checks `should_log` before calling into sinks, but combined sinks
+        // (e.g. `AsyncPoolSink`) don't check it for their sub-sinks, so check it
+        // again here to avoid formatting and sending filtered records.
+        if !self.should_log(record.level()) {
+            return Ok(());
+        }
+        // Logs are dropped until a recipient is bound.
+        if !self.bound.load(Ordering::Relaxed) || self.blocked.load(Ordering::Relaxed) {
+            return Ok(());
+        }
 
-        self.requester
-            .send_log(string_buf, self.silence().test(record.level()))
-            .map_err(|err| spdlog::Error::Downstream(err.into()))?;
-        Ok(())
+        // Decided once, so that the forwarded message sounds the same as the log,
+        // instead of finding the notify rate limit used up by the log.
+        let disable_notification = self.is_silent(record) || self.is_throttled();
+        let result = self.send_text(record, disable_notification);
+        if let Some(forward) = &self.forward
+            && forward.level_filter.test(record.level())
+        {
+            let forwarded = self
+                .requester
+                .forward_message(
+                    forward.from_chat_id,
+                    forward.message_id,
+                    disable_notification,
+                    record.level(),
+                )
+                .map(|sent| {
+                    if let Some(auto_delete) = &self.auto_delete {
+                        auto_delete.schedule(&sent);
+                    }
+                })
+                .map_err(|err| spdlog::Error::Downstream(err.into()));
+            return result.and(forwarded);
+        }
+        result
     }
 
     fn flush(&self) -> spdlog::Result<()> {
@@ -134,11 +972,65 @@ impl Sink for TelegramSink {
 /// concrete types or the number of generic types are changed in the future, it
 /// may not be considered as a breaking change.
 pub struct TelegramSinkBuilder<ArgT, ArgR> {
-    prop: SinkProp,
-    server_url: Option<Url>,
     bot_token: ArgT,
     recipient: ArgR,
+    // Moved as a whole when the required fields change the type
+    options: BuilderOptions,
+}
+
+// The options of `TelegramSinkBuilder`, other than the required fields.
+struct BuilderOptions {
+    prop: SinkProp,
+    // `None` if a custom formatter is specified
+    default_formatter: Option<DefaultFormatter>,
+    server_url: Option<Url>,
     silence: LevelFilter,
+    source_tag: Option<SourceTag>,
+    http_client: Option<reqwest::blocking::Client>,
+    content_type: Option<String>,
+    create_topic: Option<(String, Option<TopicIconColor>)>,
+    on_empty: EmptyPolicy,
+    parse_mode: Option<ParseMode>,
+    code_block_multiline: bool,
+    dry_run: Option<Box<dyn Write + Send>>,
+    retry_predicate: Option<RetryPredicate>,
+    forward: Option<Forward>,
+    raw_payload: bool,
+    follow_chat_migration: bool,
+    transport: Option<Box<dyn Transport>>,
+    auto_delete: Option<Duration>,
+    thread_id_key: Option<String>,
+    http2_prior_knowledge: bool,
+    on_blocked: BlockedPolicy,
+    category_icons: Option<CategoryIcons>,
+    allow_paid_broadcast: bool,
+    link_preview: Option<bool>,
+    environment_badge: Option<String>,
+    payload_template: Option<json::Map<String, json::Value>>,
+    force_notify: LevelFilter,
+    reply_to_if: Option<(LevelFilter, u64)>,
+    include_uptime: bool,
+    ellipsis: String,
+    tcp_nodelay: Option<bool>,
+    local_address: Option<IpAddr>,
+    notify_rate_limit: Option<Duration>,
+    collapse_long: Option<usize>,
+    recipient_rules: Vec<(LevelFilter, Recipient)>,
+    parse_mode_key: Option<String>,
+    inline_buttons: Option<ButtonsFn>,
+    allow_sending_without_reply: bool,
+    legacy_link_preview: bool,
+    ok_field: String,
+    description_field: String,
+    source_as_reply: bool,
+    thread_by_key: Option<String>,
+    max_request_bytes: usize,
+    require_topic: bool,
+    daily_topic: Option<(String, i32)>,
+    desensitize_errors: bool,
+    trim_output: bool,
+    before_send: Option<BeforeSendFn>,
+    on_latency: Option<LatencyFn>,
 }
 
 impl<ArgT, ArgD> TelegramSinkBuilder<ArgT, ArgD> {
@@ -154,10 +1046,29 @@ impl<ArgT, ArgD> TelegramSinkBuilder<ArgT, ArgD> {
     where
         S: Into<Url>,
     {
-        self.server_url = Some(url.into());
+        self.options.server_url = Some(url.into());
         self
     }
 
+    /// Specifies the Telegram Bot API server URL from a string.
+    ///
+    /// This is equivalent to [`TelegramSinkBuilder::server_url`], but parses
+    /// the URL for you.
+    ///
+    /// Returns [`Error::ParseUrl`] if the URL is invalid.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use spdlog_telegram::TelegramSink;
+    ///
+    /// TelegramSink::builder().server_url_str("http://localhost:8081")?;
+    /// # Ok::<(), spdlog_telegram::Error>(())
+    /// ```
+    pub fn server_url_str(self, url: &str) -> Result<Self> {
+        Ok(self.server_url(Url::parse(url).map_err(Error::ParseUrl)?))
+    }
+
     /// Specifies the bot token.
     ///
     /// See [Telegram Bot API: Authorizing your bot][token]
@@ -171,11 +1082,9 @@ impl<ArgT, ArgD> TelegramSinkBuilder<ArgT, ArgD> {
         T: Into<String>,
     {
         TelegramSinkBuilder {
-            prop: self.prop,
-            server_url: self.server_url,
             bot_token: bot_token.into(),
             recipient: self.recipient,
-            silence: self.silence,
+            options: self.options,
         }
     }
 
@@ -191,6 +1100,8 @@ impl<ArgT, ArgD> TelegramSinkBuilder<ArgT, ArgD> {
     /// TelegramSink::builder()
     ///     // chat ID
     ///     .recipient(-1001234567890)
+    ///     // or user ID
+    ///     .recipient(Recipient::user_id(123456789)?)
     ///     // or username
     ///     .recipient("@my_channel")
     ///     // or with thread ID
@@ -199,7 +1110,10 @@ impl<ArgT, ArgD> TelegramSinkBuilder<ArgT, ArgD> {
     ///             .username("@my_chat")
     ///             .thread_id(114)
     ///             .build()
-    ///     );
+    ///     )
+    ///     // or with thread ID, in short
+    ///     .recipient(("@my_chat", 114));
+    /// # Ok::<(), std::num::TryFromIntError>(())
     /// ```
     #[must_use]
     pub fn recipient<R>(self, recipient: R) -> TelegramSinkBuilder<ArgT, Recipient>
@@ -207,164 +1121,4119 @@ impl<ArgT, ArgD> TelegramSinkBuilder<ArgT, ArgD> {
         R: Into<Recipient>,
     {
         TelegramSinkBuilder {
-            prop: self.prop,
-            server_url: self.server_url,
             bot_token: self.bot_token,
             recipient: recipient.into(),
-            silence: self.silence,
+            options: self.options,
         }
     }
 
+    /// Specifies a recipient for logs matching the level filter, instead of
+    /// the [default recipient].
+    ///
+    /// Rules accumulate and are tested in the order they are added, the first
+    /// matching one wins. Logs matching no rule are sent to the default
+    /// recipient. Each recipient is prepared once when building the sink, so
+    /// routing costs nothing per log. [`TelegramSink::set_recipient`] only
+    /// changes the default recipient.
+    ///
+    /// This parameter is **optional**.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use spdlog::{Level, LevelFilter};
+    /// use spdlog_telegram::TelegramSink;
+    ///
+    /// TelegramSink::builder()
+    ///     // Errors page the on-call chat
+    ///     .recipient_for(LevelFilter::MoreSevereEqual(Level::Error), "@my_pager")
+    ///     // Everything else goes to the log channel
+    ///     .recipient("@my_logs");
+    /// ```
+    ///
+    /// [default recipient]: TelegramSinkBuilder::recipient
+    #[must_use]
+    pub fn recipient_for<R>(mut self, level_filter: LevelFilter, recipient: R) -> Self
+    where
+        R: Into<Recipient>,
+    {
+        self.options
+            .recipient_rules
+            .push((level_filter, recipient.into()));
+        self
+    }
+
     /// Specifies the silence level filter.
     ///
     /// Logs with level matching the filter will be sent with
-    /// `disable_notification` set to `true`.
+    /// `disable_notification` set to `true`, unless they match the
+    /// [force-notify filter].
     ///
     /// This parameter is **optional**.
+    ///
+    /// [force-notify filter]: TelegramSinkBuilder::force_notify
     #[must_use]
     pub fn silence(mut self, silent_if: LevelFilter) -> Self {
-        self.silence = silent_if;
+        self.options.silence = silent_if;
         self
     }
 
-    // Prop
-    //
+    /// Specifies all logs to be sent without notification sound.
+    ///
+    /// This is equivalent to `silence(LevelFilter::All)`.
+    #[must_use]
+    pub fn always_silent(self) -> Self {
+        self.silence(LevelFilter::All)
+    }
 
-    /// Specifies a log level filter.
+    /// Specifies all logs to be sent with notification sound.
+    ///
+    /// This is equivalent to `silence(LevelFilter::Off)`, which is the default.
+    #[must_use]
+    pub fn always_audible(self) -> Self {
+        self.silence(LevelFilter::Off)
+    }
+
+    /// Specifies the force-notify level filter.
+    ///
+    /// Logs with level matching the filter are always sent with notification
+    /// sound. It takes precedence over both the [silence level filter] and the
+    /// [silence predicate], so that e.g. everything except errors can be
+    /// muted, while errors always ring:
+    ///
+    /// ```
+    /// use spdlog::{Level, LevelFilter};
+    /// use spdlog_telegram::TelegramSink;
+    ///
+    /// TelegramSink::builder()
+    ///     .always_silent()
+    ///     .force_notify(LevelFilter::MoreSevereEqual(Level::Error));
+    /// ```
+    ///
+    /// The [notify rate limit] still applies to them.
+    ///
+    /// This parameter is **optional**, and defaults to `Off`.
+    ///
+    /// [silence level filter]: TelegramSinkBuilder::silence
+    /// [silence predicate]: TelegramSink::set_silence_fn
+    /// [notify rate limit]: TelegramSinkBuilder::notify_rate_limit
+    #[must_use]
+    pub fn force_notify(mut self, notify_if: LevelFilter) -> Self {
+        self.options.force_notify = notify_if;
+        self
+    }
+
+    /// Specifies a window in which only the first message rings.
+    ///
+    /// After a message is sent with notification sound, subsequent messages
+    /// within the window are sent without it, e.g. only the first error of a
+    /// storm buzzes the phone. The window restarts with the next audible
+    /// message after it elapses. All messages are still sent, only the sound is
+    /// throttled, after the other silence rules are applied.
     ///
     /// This parameter is **optional**.
     #[must_use]
-    pub fn level_filter(self, level_filter: LevelFilter) -> Self {
-        self.prop.set_level_filter(level_filter);
+    pub fn notify_rate_limit(mut self, window: Duration) -> Self {
+        self.options.notify_rate_limit = Some(window);
         self
     }
 
-    /// Specifies a formatter.
+    /// Specifies an environment badge to show at the top of every message,
+    /// e.g. `[PROD]`.
+    ///
+    /// The badge is rendered as `[<badge>]` on its own line, so that logs from
+    /// different environments sharing similar chats are never confused.
+    /// Include an emoji for more emphasis, e.g. `"🔴 PROD"`.
     ///
     /// This parameter is **optional**.
     #[must_use]
-    pub fn formatter<F>(self, formatter: F) -> Self
+    pub fn environment_badge<S>(mut self, badge: S) -> Self
     where
-        F: Formatter + 'static,
+        S: Into<String>,
     {
-        self.prop.set_formatter(formatter);
+        self.options.environment_badge = Some(badge.into());
         self
     }
 
-    /// Specifies an error handler.
+    /// Specifies process metadata to append to every message as a footer.
+    ///
+    /// The footer is rendered once when building the sink, e.g.
+    /// `[host=web-3 pid=4821]`. It is counted against the message length
+    /// limit, the message will be truncated to make room for it if necessary.
     ///
     /// This parameter is **optional**.
     #[must_use]
-    pub fn error_handler<F>(self, handler: F) -> Self
-    where
-        F: Into<ErrorHandler>,
-    {
-        self.prop.set_error_handler(handler);
+    pub fn source_tag(mut self, tag: SourceTag) -> Self {
+        self.options.source_tag = Some(tag);
         self
     }
-}
 
-impl<ArgR> TelegramSinkBuilder<(), ArgR> {
-    #[doc(hidden)]
-    #[deprecated(note = "\n\n\
-        builder compile-time error:\n\
-        - missing required field `bot_token`\n\n\
-    ")]
-    pub fn build(self, _: Infallible) {}
+    /// Specifies whether to append the uptime of the sink to every message,
+    /// e.g. `uptime=2h13m`.
+    ///
+    /// The uptime is measured from when the sink is built, which is usually
+    /// close to the start of the process. It helps to tell startup crashes from
+    /// failures after a long run. It's rendered in the same footer as the
+    /// [source tag], e.g. `[pid=4821 uptime=2h13m]`, and counted against the
+    /// message length limit too.
+    ///
+    /// This parameter is **optional**, and defaults to `false`.
+    ///
+    /// [source tag]: TelegramSinkBuilder::source_tag
+    #[must_use]
+    pub fn include_uptime(mut self, include: bool) -> Self {
+        self.options.include_uptime = include;
+        self
+    }
+
+    /// Specifies the HTTP client used to send requests.
+    ///
+    /// By default, each sink builds its own client with its own connection
+    /// pool. [`reqwest::blocking::Client`] uses an `Arc` internally, so clones
+    /// of a client share the same pool. If you have multiple sinks, e.g. for
+    /// different recipients with the same bot, you may want to build one
+    /// client and pass a clone of it to each sink.
+    ///
+    /// This parameter is **optional**.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use spdlog_telegram::TelegramSink;
+    ///
+    /// let client = reqwest::blocking::Client::new();
+    /// let (alerts, logs) = (
+    ///     TelegramSink::builder()
+    ///         .http_client(client.clone())
+    ///         .bot_token("1234567890:AbCdEfGhiJkLmNoPq1R2s3T4u5V6w7X8y9z")
+    ///         .recipient("@my_alerts")
+    ///         .build()?,
+    ///     TelegramSink::builder()
+    ///         .http_client(client)
+    ///         .bot_token("1234567890:AbCdEfGhiJkLmNoPq1R2s3T4u5V6w7X8y9z")
+    ///         .recipient("@my_logs")
+    ///         .build()?,
+    /// );
+    /// # Ok::<(), spdlog_telegram::Error>(())
+    /// ```
+    #[must_use]
+    pub fn http_client(mut self, client: reqwest::blocking::Client) -> Self {
+        self.options.http_client = Some(client);
+        self
+    }
+
+    /// Specifies whether to use HTTP/2 with prior knowledge, i.e. without
+    /// negotiation.
+    ///
+    /// This reduces overhead for high-throughput deployments of a [local Bot
+    /// API server][server_url] supporting it. It only takes effect when a
+    /// custom server URL is specified, as the official server requires
+    /// negotiation. It has no effect if a custom [HTTP client] is specified,
+    /// configure the client instead.
+    ///
+    /// This parameter is **optional**, and defaults to `false`.
+    ///
+    /// [server_url]: TelegramSinkBuilder::server_url
+    /// [HTTP client]: TelegramSinkBuilder::http_client
+    #[must_use]
+    pub fn http2_prior_knowledge(mut self, enabled: bool) -> Self {
+        self.options.http2_prior_knowledge = enabled;
+        self
+    }
+
+    /// Specifies whether to set `TCP_NODELAY` on connections, see
+    /// [`reqwest::blocking::ClientBuilder::tcp_nodelay`].
+    ///
+    /// It has no effect if a custom [HTTP client] is specified, configure the
+    /// client instead.
+    ///
+    /// This parameter is **optional**, and defaults to reqwest's default.
+    ///
+    /// [HTTP client]: TelegramSinkBuilder::http_client
+    #[must_use]
+    pub fn tcp_nodelay(mut self, enabled: bool) -> Self {
+        self.options.tcp_nodelay = Some(enabled);
+        self
+    }
+
+    /// Specifies the local address to bind connections to, see
+    /// [`reqwest::blocking::ClientBuilder::local_address`].
+    ///
+    /// This is useful on multi-homed hosts, to send logs from a specific
+    /// network interface. It has no effect if a custom [HTTP client] is
+    /// specified, configure the client instead.
+    ///
+    /// This parameter is **optional**.
+    ///
+    /// [HTTP client]: TelegramSinkBuilder::http_client
+    #[must_use]
+    pub fn local_address(mut self, address: IpAddr) -> Self {
+        self.options.local_address = Some(address);
+        self
+    }
+
+    /// Specifies the `Content-Type` header of requests.
+    ///
+    /// Some intercepting proxies reject the default value or require a charset
+    /// suffix, e.g. `"application/json; charset=utf-8"`. Pass `None` to omit
+    /// the header entirely. The body is always JSON regardless.
+    ///
+    /// This parameter is **optional**.
+    #[must_use]
+    pub fn content_type(mut self, content_type: Option<&str>) -> Self {
+        self.options.content_type = content_type.map(str::to_string);
+        self
+    }
+
+    /// Specifies to create a new forum topic when building the sink, and send
+    /// all logs to it.
+    ///
+    /// The topic is created in the recipient chat via `createForumTopic`, the
+    /// returned thread ID overrides the thread ID of the recipient. The bot
+    /// must have the `can_manage_topics` administrator right in the chat.
+    ///
+    /// If the creation fails, [`TelegramSinkBuilder::build`] returns an error.
+    ///
+    /// This parameter is **optional**.
+    #[must_use]
+    pub fn create_topic<S>(mut self, name: S, icon_color: Option<TopicIconColor>) -> Self
+    where
+        S: Into<String>,
+    {
+        self.options.create_topic = Some((name.into(), icon_color));
+        self
+    }
+
+    /// Specifies whether to require a topic if the recipient chat is a forum.
+    ///
+    /// Sending to a forum supergroup without a thread ID posts to the General
+    /// topic, which is usually a misconfiguration. If enabled, building the
+    /// sink fails with [`Error::TopicRequired`] for a forum recipient without a
+    /// [thread ID] nor a [topic to create]. It also applies to
+    /// [`TelegramSink::set_recipient`] and
+    /// [`TelegramSinkBuilder::recipient_for`].
+    ///
+    /// Detecting forums requires an extra [`getChat`] call per recipient
+    /// without a thread ID when building the sink, so it's opt-in. Thread IDs
+    /// of [`TelegramSinkBuilder::thread_id_key`] are not considered, as they
+    /// are only known when logging.
+    ///
+    /// This parameter is **optional**, and defaults to `false`.
+    ///
+    /// [thread ID]: Recipient::builder
+    /// [topic to create]: TelegramSinkBuilder::create_topic
+    /// [`getChat`]: https://core.telegram.org/bots/api#getchat
+    #[must_use]
+    pub fn require_topic(mut self, require: bool) -> Self {
+        self.options.require_topic = require;
+        self
+    }
+
+    /// Specifies to send logs to a forum topic created per day.
+    ///
+    /// A topic named by `name_fmt` is created via [`createForumTopic`] on the
+    /// first log of each day, with `{date}` replaced by the date as
+    /// `YYYY-MM-DD`. Days roll over at midnight in the time zone of the given
+    /// UTC offset, in seconds east of UTC, e.g. `8 * 3600` for UTC+8. Daylight
+    /// saving time is not taken into account.
+    ///
+    /// The thread IDs of the topics are kept in memory per chat, so a topic
+    /// is created again for the day after the sink is rebuilt, e.g. on
+    /// restart. The bot must have permission to manage topics. If creating
+    /// the topic fails, it's reported to the error handler, the log is sent
+    /// to the thread of the recipient, e.g. the General topic, and creating is
+    /// retried a minute later. It takes precedence over the thread of the
+    /// [recipient] and [`TelegramSinkBuilder::create_topic`], but not over
+    /// [`TelegramSinkBuilder::thread_id_key`].
+    ///
+    /// This parameter is **optional**.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use spdlog_telegram::TelegramSink;
+    ///
+    /// // Creates topics such as "logs 2026-10-14", rolling over at midnight UTC+8
+    /// TelegramSink::builder().daily_topic("logs {date}", 8 * 3600);
+    /// ```
+    ///
+    /// [`createForumTopic`]: https://core.telegram.org/bots/api#createforumtopic
+    /// [recipient]: TelegramSinkBuilder::recipient
+    #[must_use]
+    pub fn daily_topic<S>(mut self, name_fmt: S, utc_offset: i32) -> Self
+    where
+        S: Into<String>,
+    {
+        self.options.daily_topic = Some((name_fmt.into(), utc_offset));
+        self
+    }
+
+    /// Specifies the behavior when the formatted text of a log is empty or
+    /// whitespace-only.
+    ///
+    /// This parameter is **optional**.
+    #[must_use]
+    pub fn on_empty(mut self, policy: EmptyPolicy) -> Self {
+        self.options.on_empty = policy;
+        self
+    }
+
+    /// Specifies the leading tag of messages, or `None` to drop it.
+    ///
+    /// The leading `#` is optional, e.g. both `"myapp"` and `"#myapp"` result
+    /// in `#myapp`. The `#{level}` tag is not affected. This only takes effect
+    /// with the default formatter.
+    ///
+    /// This parameter is **optional**, and defaults to `Some("log")`.
+    #[must_use]
+    pub fn log_tag(mut self, tag: Option<&str>) -> Self {
+        if let Some(formatter) = &mut self.options.default_formatter {
+            formatter.log_tag = tag.map(|tag| tag.trim_start_matches('#').to_string());
+        }
+        self
+    }
+
+    /// Specifies whether to prepend the logger name to messages, as
+    /// `[<logger_name>]`.
+    ///
+    /// This helps to distinguish logs from multiple loggers sharing the sink.
+    /// Logs from loggers without a name are not affected. This only takes
+    /// effect with the default formatter.
+    ///
+    /// This parameter is **optional**, and defaults to `false`.
+    #[must_use]
+    pub fn include_logger_name(mut self, include: bool) -> Self {
+        if let Some(formatter) = &mut self.options.default_formatter {
+            formatter.logger_name = include;
+        }
+        self
+    }
+
+    /// Specifies whether to render the level as a bold badge with its color,
+    /// e.g. `<b>🔴 ERROR</b>`, instead of a hashtag.
+    ///
+    /// This makes HTML channels much easier to scan. The rest of the message,
+    /// e.g. the payload and key-values, is HTML-escaped. The colors are the
+    /// same as [`level_color`].
+    ///
+    /// This only takes effect with the default formatter, and if the [parse
+    /// mode] is [`ParseMode::Html`].
+    ///
+    /// This parameter is **optional**, and defaults to `false`.
+    ///
+    /// [parse mode]: TelegramSinkBuilder::parse_mode
+    #[must_use]
+    pub fn html_level_badge(mut self, enabled: bool) -> Self {
+        if let Some(formatter) = &mut self.options.default_formatter {
+            formatter.html_level_badge = enabled;
+        }
+        self
+    }
+
+    /// Specifies the parse mode of message texts.
+    ///
+    /// The formatted text is sent as-is, so the formatter is responsible for
+    /// producing valid markup, e.g. escaping the payload. Crate-added parts
+    /// such as the [source tag] are escaped automatically.
+    ///
+    /// This parameter is **optional**, and defaults to plain text. Note that it
+    /// also changes the default of [link previews].
+    ///
+    /// [source tag]: TelegramSinkBuilder::source_tag
+    /// [link previews]: TelegramSinkBuilder::link_preview
+    #[must_use]
+    pub fn parse_mode(mut self, parse_mode: ParseMode) -> Self {
+        self.options.parse_mode = Some(parse_mode);
+        self
+    }
+
+    /// Specifies a key-value key whose value overrides the [parse mode] for the
+    /// log.
+    ///
+    /// This allows a sink to handle mixed formatting, e.g. plain text logs with
+    /// a few pre-formatted ones. The value is one of `"HTML"`, `"MarkdownV2"`
+    /// or `"none"` for plain text, case-insensitively. If the key-value is
+    /// missing or the value is unknown, the configured parse mode is used. The
+    /// formatter output of such logs must be valid in the overridden mode.
+    ///
+    /// This parameter is **optional**.
+    ///
+    /// ## Examples
+    ///
+    /// ```no_run
+    /// use std::sync::Arc;
+    ///
+    /// use spdlog::prelude::*;
+    /// use spdlog_telegram::TelegramSink;
+    ///
+    /// let sink = TelegramSink::builder()
+    ///     .bot_token("1234567890:AbCdEfGhiJkLmNoPq1R2s3T4u5V6w7X8y9z")
+    ///     .recipient(-1001234567890)
+    ///     .parse_mode_key("parse_mode")
+    ///     .build()?;
+    /// let logger = Logger::builder().sink(Arc::new(sink)).build()?;
+    ///
+    /// // Sent as plain text
+    /// info!(logger: logger, "deployed v1.2.3");
+    /// // Sent as MarkdownV2
+    /// info!(logger: logger, "*deployed* v1\\.2\\.3", kv: { parse_mode = "MarkdownV2" });
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// [parse mode]: TelegramSinkBuilder::parse_mode
+    #[must_use]
+    pub fn parse_mode_key<S>(mut self, key: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.options.parse_mode_key = Some(key.into());
+        self
+    }
+
+    /// Specifies whether to trim whitespace around the formatted text.
+    ///
+    /// Formatters often leave trailing newlines or spaces, e.g. the default
+    /// formatter after a payload without key-values, which Telegram may render
+    /// as blank lines. The formatter output is trimmed before anything is
+    /// added by this crate. Whitespace inside the text is kept, and so is the
+    /// indentation of the first line if it's put in a [code block]. Texts with
+    /// a [parse mode] are trimmed around the markup, so the content of code
+    /// blocks written by the formatter is not affected. It has no effect with
+    /// [`TelegramSinkBuilder::raw_payload`].
+    ///
+    /// This parameter is **optional**, and defaults to `true`.
+    ///
+    /// [code block]: TelegramSinkBuilder::code_block_multiline
+    /// [parse mode]: TelegramSinkBuilder::parse_mode
+    #[must_use]
+    pub fn trim_output(mut self, enabled: bool) -> Self {
+        self.options.trim_output = enabled;
+        self
+    }
+
+    /// Specifies a function called with the JSON payload of `sendMessage` for
+    /// every log, right before it's sent.
+    ///
+    /// This is an escape hatch for fields of the [Bot API] this crate doesn't
+    /// support yet, or for rewriting the payload in ways the other options
+    /// can't. The text and all other fields are already set when it's called.
+    /// Returning `false` drops the log, which is then neither counted as
+    /// delivered nor as failed in [`TelegramSink::stats`].
+    ///
+    /// Mutating or removing fields set by this crate, e.g. `chat_id`, `text`
+    /// or `parse_mode`, may break the delivery of logs. The function is also
+    /// called for the test message sent by [`build_and_test`] and for
+    /// [source replies], but not for other methods of the Bot API.
+    ///
+    /// This parameter is **optional**.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use spdlog_telegram::TelegramSink;
+    ///
+    /// TelegramSink::builder().before_send(|payload| {
+    ///     payload["protect_content"] = true.into();
+    ///     true
+    /// });
+    /// ```
+    ///
+    /// [Bot API]: https://core.telegram.org/bots/api#sendmessage
+    /// [`build_and_test`]: TelegramSinkBuilder::build_and_test
+    /// [source replies]: TelegramSinkBuilder::source_as_reply
+    #[must_use]
+    pub fn before_send<F>(mut self, before_send: F) -> Self
+    where
+        F: Fn(&mut json::Value) -> bool + Send + Sync + 'static,
+    {
+        self.options.before_send = Some(Box::new(before_send));
+        self
+    }
+
+    /// Specifies a function called with the round-trip time of every
+    /// `sendMessage` request, along with the `chat_id` it's sent to.
+    ///
+    /// This helps to tell slowness of Telegram or the network apart from
+    /// slowness of the application, e.g. for SLO tracking. Only the call of
+    /// the [transport] is timed, from sending the request to receiving the
+    /// response, so delays between [retries] are excluded and each attempt is
+    /// reported separately. Failed requests are reported too, e.g. timeouts.
+    /// The chat ID tells the recipients apart if [recipient rules] are used,
+    /// it's a number, or a string for usernames. It's not called in [dry-run
+    /// mode].
+    ///
+    /// The function is called on the logging thread, so it should be cheap.
+    ///
+    /// This parameter is **optional**.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use spdlog_telegram::TelegramSink;
+    ///
+    /// TelegramSink::builder().on_latency(|latency, chat_id| {
+    ///     if latency.as_secs() >= 3 {
+    ///         eprintln!("sending to {chat_id} took {latency:?}");
+    ///     }
+    /// });
+    /// ```
+    ///
+    /// [transport]: TelegramSinkBuilder::transport
+    /// [retries]: TelegramSinkBuilder::retry_predicate
+    /// [recipient rules]: TelegramSinkBuilder::recipient_for
+    /// [dry-run mode]: TelegramSinkBuilder::dry_run_writer
+    #[must_use]
+    pub fn on_latency<F>(mut self, on_latency: F) -> Self
+    where
+        F: Fn(Duration, &json::Value) + Send + Sync + 'static,
+    {
+        self.options.on_latency = Some(Box::new(on_latency));
+        self
+    }
+
+    /// Specifies whether to show link previews in messages.
+    ///
+    /// This parameter is **optional**. By default, link previews are disabled
+    /// for plain text and enabled if a [parse mode] is specified, as formatted
+    /// messages usually contain deliberate links, e.g. to a runbook.
+    ///
+    /// [parse mode]: TelegramSinkBuilder::parse_mode
+    #[must_use]
+    pub fn link_preview(mut self, enabled: bool) -> Self {
+        self.options.link_preview = Some(enabled);
+        self
+    }
+
+    /// Specifies whether to send the deprecated `disable_web_page_preview`
+    /// field instead of `link_preview_options`.
+    ///
+    /// Bot API 7.0 replaced `disable_web_page_preview` with
+    /// `link_preview_options`. Enable this for self-hosted Bot API servers
+    /// older than 7.0 that reject the newer field. The [link preview] option
+    /// is honored either way.
+    ///
+    /// This parameter is **optional**, and defaults to `false`.
+    ///
+    /// [link preview]: TelegramSinkBuilder::link_preview
+    #[must_use]
+    pub fn legacy_link_preview(mut self, enabled: bool) -> Self {
+        self.options.legacy_link_preview = enabled;
+        self
+    }
+
+    /// Specifies whether to wrap multi-line texts in a code block.
+    ///
+    /// Multi-line logs such as panics with backtraces are much more readable
+    /// in a code block. The text is escaped and wrapped in a `<pre>` block in
+    /// HTML, or a ```` ``` ```` block in MarkdownV2, matching the configured
+    /// [parse mode]. Texts in plain mode are sent as HTML, a ```` ``` ````
+    /// block in them is converted to a `<pre>` block. Texts that are already
+    /// wrapped in a code block are not wrapped again.
+    ///
+    /// This parameter is **optional**, and defaults to `false`.
+    ///
+    /// [parse mode]: TelegramSinkBuilder::parse_mode
+    #[must_use]
+    pub fn code_block_multiline(mut self, enabled: bool) -> Self {
+        self.options.code_block_multiline = enabled;
+        self
+    }
+
+    /// Specifies a length threshold above which texts are collapsed into an
+    /// expandable blockquote.
+    ///
+    /// Long context such as backtraces is then hidden behind a tap, keeping
+    /// the chat scannable. The length is measured in UTF-16 code units. Texts
+    /// with a [parse mode] are wrapped as-is, so the formatter is still
+    /// responsible for valid markup. Texts in plain mode are escaped and sent
+    /// as HTML. Collapsed texts are not wrapped in a [code block] too.
+    ///
+    /// This parameter is **optional**, and defaults to never collapsing.
+    ///
+    /// [parse mode]: TelegramSinkBuilder::parse_mode
+    /// [code block]: TelegramSinkBuilder::code_block_multiline
+    #[must_use]
+    pub fn collapse_long(mut self, threshold: usize) -> Self {
+        self.options.collapse_long = Some(threshold);
+        self
+    }
+
+    /// Specifies whether to write requests to stdout instead of sending them.
+    ///
+    /// In dry-run mode, no HTTP request is made. Each request is written as a
+    /// line prefixed with `[spdlog-telegram dry-run]`, followed by the Bot API
+    /// method and the JSON payload, and is treated as successful. The bot
+    /// token is never written. Useful for debugging the formatter and the
+    /// recipient configuration.
+    ///
+    /// Use [`TelegramSinkBuilder::dry_run_writer`] to write to somewhere else.
+    ///
+    /// This parameter is **optional**, and defaults to `false`.
+    #[must_use]
+    pub fn dry_run(mut self, enabled: bool) -> Self {
+        self.options.dry_run = enabled.then(|| Box::new(io::stdout()) as Box<dyn Write + Send>);
+        self
+    }
+
+    /// Enables [dry-run mode], and specifies the writer to write requests to.
+    ///
+    /// This parameter is **optional**.
+    ///
+    /// [dry-run mode]: TelegramSinkBuilder::dry_run
+    #[must_use]
+    pub fn dry_run_writer<W>(mut self, writer: W) -> Self
+    where
+        W: Write + Send + 'static,
+    {
+        self.options.dry_run = Some(Box::new(writer));
+        self
+    }
+
+    /// Specifies whether and when to retry a failed send.
+    ///
+    /// The predicate is called with the error and the number of attempts made
+    /// so far (starting from `1`), returns `Some(delay)` to retry after the
+    /// delay, or `None` to give up and report the error to the error handler.
+    ///
+    /// Note that the delay blocks the logging thread, consider using
+    /// [`TelegramSink::into_async_pool`] together.
+    ///
+    /// This parameter is **optional**, and by default failed sends are not
+    /// retried.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use spdlog_telegram::{Error, TelegramSink};
+    ///
+    /// TelegramSink::builder().retry_predicate(|err, attempt| match err {
+    ///     Error::SendRequest(_) if attempt < 3 => Some(Duration::from_secs(1 << attempt)),
+    ///     _ => None,
+    /// });
+    /// ```
+    #[must_use]
+    pub fn retry_predicate<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&Error, u32) -> Option<Duration> + Send + Sync + 'static,
+    {
+        self.options.retry_predicate = Some(Box::new(predicate));
+        self
+    }
+
+    /// Specifies an existing message to forward to the recipient for logs
+    /// matching the level filter.
+    ///
+    /// The message is forwarded via `forwardMessage` after the log itself is
+    /// sent, e.g. a pre-authored runbook for critical logs. Forwarded
+    /// messages follow the [silence] rules of the log.
+    ///
+    /// This parameter is **optional**.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use spdlog::prelude::*;
+    /// use spdlog_telegram::TelegramSink;
+    ///
+    /// TelegramSink::builder().forward_on(
+    ///     LevelFilter::MoreSevereEqual(Level::Critical),
+    ///     -1001234567890,
+    ///     42,
+    /// );
+    /// ```
+    ///
+    /// [silence]: TelegramSinkBuilder::silence
+    #[must_use]
+    pub fn forward_on(
+        mut self,
+        level_filter: LevelFilter,
+        from_chat_id: i64,
+        message_id: u64,
+    ) -> Self {
+        self.options.forward = Some(Forward {
+            level_filter,
+            from_chat_id,
+            message_id,
+        });
+        self
+    }
+
+    /// Specifies a message in the recipient chat to reply to for logs matching
+    /// the level filter.
+    ///
+    /// This is useful for incident threading, e.g. error logs reply to a pinned
+    /// "incidents" message, while other logs are posted normally. For matching
+    /// logs, it takes precedence over the reply configured in the
+    /// [recipient]. Other logs are not affected.
+    ///
+    /// This parameter is **optional**.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use spdlog::prelude::*;
+    /// use spdlog_telegram::TelegramSink;
+    ///
+    /// TelegramSink::builder().reply_to_if(LevelFilter::MoreSevereEqual(Level::Error), 514);
+    /// ```
+    ///
+    /// [recipient]: TelegramSinkBuilder::recipient
+    #[must_use]
+    pub fn reply_to_if(mut self, level_filter: LevelFilter, message_id: u64) -> Self {
+        self.options.reply_to_if = Some((level_filter, message_id));
+        self
+    }
+
+    /// Specifies whether to send logs even if the message they reply to is not
+    /// found, e.g. deleted.
+    ///
+    /// If enabled, `allow_sending_without_reply` is set in the
+    /// `reply_parameters` of all replies, i.e. of the [recipient] and of
+    /// [`TelegramSinkBuilder::reply_to_if`], so that logs are sent as regular
+    /// messages instead of failing.
+    ///
+    /// This parameter is **optional**, and defaults to `false`, the same as
+    /// Telegram.
+    ///
+    /// [recipient]: TelegramSinkBuilder::recipient
+    #[must_use]
+    pub fn allow_sending_without_reply(mut self, allow: bool) -> Self {
+        self.options.allow_sending_without_reply = allow;
+        self
+    }
+
+    /// Specifies whether to send the source location of a log as a reply to
+    /// its message, instead of within it.
+    ///
+    /// This keeps the primary message clean while preserving the source
+    /// context in a threaded reply. The source location is omitted from the
+    /// message of the default formatter, and sent as `@{file}:{line}` without
+    /// notification sound. Custom [formatters] are responsible for omitting it
+    /// themselves.
+    ///
+    /// No reply is sent for logs without a source location, i.e. if crate
+    /// feature `source-location` of spdlog-rs is not enabled, or in [dry-run
+    /// mode], as there is no message to reply to.
+    ///
+    /// This parameter is **optional**, and defaults to `false`.
+    ///
+    /// [formatters]: TelegramSinkBuilder::formatter
+    /// [dry-run mode]: TelegramSinkBuilder::dry_run
+    #[must_use]
+    pub fn source_as_reply(mut self, enabled: bool) -> Self {
+        self.options.source_as_reply = enabled;
+        self
+    }
+
+    /// Specifies a function returning rows of inline keyboard buttons to
+    /// attach under the message for a log.
+    ///
+    /// This makes alerts actionable, e.g. with a button opening the dashboard.
+    /// Returning `None` or no rows attaches nothing. [URL buttons] are the
+    /// most useful, as this crate doesn't receive updates, so [callback
+    /// buttons] need an external handler.
+    ///
+    /// This parameter is **optional**.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use spdlog::{Level, LevelFilter};
+    /// use spdlog_telegram::{Button, TelegramSink};
+    ///
+    /// TelegramSink::builder().inline_buttons(|record| {
+    ///     LevelFilter::MoreSevereEqual(Level::Error)
+    ///         .test(record.level())
+    ///         .then(|| {
+    ///             vec![vec![Button::url(
+    ///                 "Open dashboard",
+    ///                 "https://grafana.example.com/d/api",
+    ///             )]]
+    ///         })
+    /// });
+    /// ```
+    ///
+    /// [URL buttons]: Button::url
+    /// [callback buttons]: Button::callback
+    #[must_use]
+    pub fn inline_buttons<F>(mut self, inline_buttons: F) -> Self
+    where
+        F: Fn(&Record) -> Option<Vec<Vec<Button>>> + Send + Sync + 'static,
+    {
+        self.options.inline_buttons = Some(Box::new(inline_buttons));
+        self
+    }
+
+    /// Specifies whether to send the formatter output verbatim as the message
+    /// text.
+    ///
+    /// If enabled, nothing is added by this crate, i.e. the [source tag] and
+    /// [code blocks] are omitted, and the default formatter writes only the
+    /// payload, without the logger name, tags, key-values and source location.
+    /// A custom [formatter] is used as-is. Useful for messages pre-formatted
+    /// elsewhere.
+    ///
+    /// This parameter is **optional**, and defaults to `false`.
+    ///
+    /// [source tag]: TelegramSinkBuilder::source_tag
+    /// [code blocks]: TelegramSinkBuilder::code_block_multiline
+    /// [formatter]: TelegramSinkBuilder::formatter
+    #[must_use]
+    pub fn raw_payload(mut self, enabled: bool) -> Self {
+        if let Some(formatter) = &mut self.options.default_formatter {
+            formatter.payload_only = enabled;
+        }
+        self.options.raw_payload = enabled;
+        self
+    }
+
+    /// Specifies whether to follow the recipient group when it's migrated to a
+    /// supergroup.
+    ///
+    /// When a group is upgraded to a supergroup, its chat ID changes and sends
+    /// to the old ID fail with [`Error::ChatMigrated`]. If enabled, the sink
+    /// switches to the new chat ID and sends the log again, for all
+    /// [recipients] in the group. Consider updating the configured recipients
+    /// anyway, as the switch is not persisted.
+    ///
+    /// This parameter is **optional**, and defaults to `false`.
+    ///
+    /// [recipients]: TelegramSinkBuilder::recipient_for
+    #[must_use]
+    pub fn follow_chat_migration(mut self, follow: bool) -> Self {
+        self.options.follow_chat_migration = follow;
+        self
+    }
+
+    /// Specifies which logs to send and which of them to notify with sound.
+    ///
+    /// This is equivalent to setting [`TelegramSinkBuilder::level_filter`] to
+    /// `LevelFilter::MoreSevereEqual(min_level_to_send)` and
+    /// [`TelegramSinkBuilder::silence`] to
+    /// `LevelFilter::MoreVerbose(min_level_for_sound)`, and overrides them.
+    ///
+    /// This parameter is **optional**.
+    #[must_use]
+    pub fn notify_policy(self, policy: NotifyPolicy) -> Self {
+        self.silence(LevelFilter::MoreVerbose(policy.min_level_for_sound))
+            .level_filter(LevelFilter::MoreSevereEqual(policy.min_level_to_send))
+    }
+
+    /// Specifies a custom transport to deliver requests.
+    ///
+    /// If specified, parameters for the default HTTP transport are ignored,
+    /// i.e. [server_url], [http_client] and [content_type]. The bot token is
+    /// still required, but is not passed to the transport.
+    ///
+    /// This parameter is **optional**, and defaults to sending requests to the
+    /// Bot API server over HTTP.
+    ///
+    /// [server_url]: TelegramSinkBuilder::server_url
+    /// [http_client]: TelegramSinkBuilder::http_client
+    /// [content_type]: TelegramSinkBuilder::content_type
+    #[must_use]
+    pub fn transport<T>(mut self, transport: T) -> Self
+    where
+        T: Transport + 'static,
+    {
+        self.options.transport = Some(Box::new(transport));
+        self
+    }
+
+    /// Specifies the maximum size of a serialized request body, in bytes.
+    ///
+    /// Requests exceeding it fail with [`Error::RequestTooLarge`] before being
+    /// sent, which guards against runaway logs, e.g. a huge
+    /// [payload template]. It applies to all Bot API requests of the sink sent
+    /// by the default HTTP transport or written in [dry-run] mode. Custom
+    /// [transports] receive the payload unserialized and are not limited.
+    ///
+    /// This parameter is **optional**, and defaults to 50 MiB, the same as the
+    /// upload limit of Telegram for bots.
+    ///
+    /// [payload template]: TelegramSinkBuilder::payload_template
+    /// [dry-run]: TelegramSinkBuilder::dry_run
+    /// [transports]: TelegramSinkBuilder::transport
+    #[must_use]
+    pub fn max_request_bytes(mut self, max_bytes: usize) -> Self {
+        self.options.max_request_bytes = max_bytes;
+        self
+    }
+
+    /// Specifies whether to remove URLs from errors of the HTTP client.
+    ///
+    /// **Security:** URLs of requests contain the bot token. If disabled,
+    /// [`Error::SendRequest`] keeps the URL, so **the bot token leaks into
+    /// wherever errors are reported**, e.g. log files or error trackers,
+    /// and anyone with the token can control the bot. Only disable it for
+    /// local debugging, e.g. against a local Bot API server, never in
+    /// production.
+    ///
+    /// It has no effect if a custom [transport] is specified.
+    ///
+    /// This parameter is **optional**, and defaults to `true`.
+    ///
+    /// [transport]: TelegramSinkBuilder::transport
+    #[must_use]
+    pub fn desensitize_errors(mut self, desensitize: bool) -> Self {
+        self.options.desensitize_errors = desensitize;
+        self
+    }
+
+    /// Specifies the name of the field indicating success in responses.
+    ///
+    /// This is an interop option for gateways proxying the Bot API that rename
+    /// the fields of responses, e.g. `ok` to `success`. Responses without the
+    /// field are still reported as [`Error::UnexpectedResponse`]. It applies
+    /// to custom [transports] too.
+    ///
+    /// This parameter is **optional**, and defaults to `"ok"`.
+    ///
+    /// [transports]: TelegramSinkBuilder::transport
+    #[must_use]
+    pub fn ok_field(mut self, field: &str) -> Self {
+        self.options.ok_field = field.into();
+        self
+    }
+
+    /// Specifies the name of the field holding the error description in
+    /// responses.
+    ///
+    /// See [`TelegramSinkBuilder::ok_field`].
+    ///
+    /// This parameter is **optional**, and defaults to `"description"`.
+    #[must_use]
+    pub fn description_field(mut self, field: &str) -> Self {
+        self.options.description_field = field.into();
+        self
+    }
+
+    /// Specifies to delete sent messages after the given time.
+    ///
+    /// Telegram doesn't support a TTL for bot messages, so deletions are
+    /// scheduled and issued via `deleteMessage` in a background thread. This
+    /// is useful to keep ephemeral debug chats clean. The bot must have
+    /// permission to delete messages in the chat, and Telegram only allows
+    /// deleting messages sent less than 48 hours ago.
+    ///
+    /// All messages sent by the sink are deleted, i.e. logs, [forwarded
+    /// messages], [source replies] and the test message of
+    /// [`TelegramSinkBuilder::build_and_test`]. Pending deletions are kept in
+    /// memory, they are abandoned when the sink is dropped. Deletion failures
+    /// are reported to the error handler, but have no effect on logging.
+    ///
+    /// This parameter is **optional**.
+    ///
+    /// [forwarded messages]: TelegramSinkBuilder::forward_on
+    /// [source replies]: TelegramSinkBuilder::source_as_reply
+    #[must_use]
+    pub fn auto_delete(mut self, ttl: Duration) -> Self {
+        self.options.auto_delete = Some(ttl);
+        self
+    }
+
+    /// Specifies a key-value key whose value overrides the thread ID of the
+    /// recipient for the log.
+    ///
+    /// This allows routing logs to forum topics by data carried on records,
+    /// e.g. a topic per tenant. If the key-value is missing or not an unsigned
+    /// integer, the thread ID of the recipient is used.
+    ///
+    /// This parameter is **optional**.
+    ///
+    /// ## Examples
+    ///
+    /// ```no_run
+    /// use std::sync::Arc;
+    ///
+    /// use spdlog::prelude::*;
+    /// use spdlog_telegram::TelegramSink;
+    ///
+    /// let sink = TelegramSink::builder()
+    ///     .bot_token("1234567890:AbCdEfGhiJkLmNoPq1R2s3T4u5V6w7X8y9z")
+    ///     .recipient(-1001234567890)
+    ///     .thread_id_key("topic")
+    ///     .build()?;
+    /// let logger = Logger::builder().sink(Arc::new(sink)).build()?;
+    ///
+    /// // Sent to the topic with thread ID 114
+    /// error!(logger: logger, "payment failed", kv: { topic = 114 });
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[must_use]
+    pub fn thread_id_key<S>(mut self, key: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.options.thread_id_key = Some(key.into());
+        self
+    }
+
+    /// Specifies the key of a key-value to thread logs by, e.g. a trace ID.
+    ///
+    /// The first log with a value of the key is sent as usual, subsequent logs
+    /// with the same value are sent as replies to it, forming a visual thread
+    /// per value, e.g. per request in distributed tracing. It takes precedence
+    /// over [`TelegramSinkBuilder::reply_to_if`].
+    ///
+    /// The message IDs of the first logs are kept in memory for the latest
+    /// 1024 values, the least recently used one is forgotten first, and a log
+    /// with it starts a new thread. Nothing is remembered in [dry-run mode],
+    /// as there are no messages to reply to.
+    ///
+    /// This parameter is **optional**.
+    ///
+    /// ## Examples
+    ///
+    /// ```no_run
+    /// use std::sync::Arc;
+    ///
+    /// use spdlog::prelude::*;
+    /// use spdlog_telegram::TelegramSink;
+    ///
+    /// let sink = TelegramSink::builder()
+    ///     .bot_token("1234567890:AbCdEfGhiJkLmNoPq1R2s3T4u5V6w7X8y9z")
+    ///     .recipient(-1001234567890)
+    ///     .thread_by_key("trace_id")
+    ///     .build()?;
+    /// let logger = Logger::builder().sink(Arc::new(sink)).build()?;
+    ///
+    /// error!(logger: logger, "payment failed", kv: { trace_id = "4bf92f35" });
+    /// // Sent as a reply to the message above
+    /// error!(logger: logger, "refund failed", kv: { trace_id = "4bf92f35" });
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// [dry-run mode]: TelegramSinkBuilder::dry_run
+    #[must_use]
+    pub fn thread_by_key<S>(mut self, key: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.options.thread_by_key = Some(key.into());
+        self
+    }
+
+    /// Specifies the behavior when the recipient user has blocked the bot.
+    ///
+    /// This parameter is **optional**.
+    #[must_use]
+    pub fn on_blocked(mut self, policy: BlockedPolicy) -> Self {
+        self.options.on_blocked = policy;
+        self
+    }
+
+    /// Specifies icons to prepend to messages, based on the value of a
+    /// key-value.
+    ///
+    /// Unlike [`level_color`], this is independent of the level, e.g. to tell
+    /// deploy logs from security logs at a glance in a mixed chat. Logs
+    /// without the key-value or with an unmapped value get no icon.
+    ///
+    /// This parameter is **optional**.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    ///
+    /// use spdlog_telegram::TelegramSink;
+    ///
+    /// TelegramSink::builder().category_icons(
+    ///     "category",
+    ///     HashMap::from([
+    ///         ("deploy".into(), "🚀".into()),
+    ///         ("security".into(), "🛡️".into()),
+    ///     ]),
+    /// );
+    /// ```
+    #[must_use]
+    pub fn category_icons(mut self, key: &str, icons: HashMap<String, String>) -> Self {
+        self.options.category_icons = Some(CategoryIcons {
+            key: key.to_string(),
+            icons,
+        });
+        self
+    }
+
+    /// Specifies whether to allow paid broadcasts for logs.
+    ///
+    /// If enabled, the sink can send up to 1000 messages per second, ignoring
+    /// the broadcasting limits (about 30 messages per second), see
+    /// [`allow_paid_broadcast`][api].
+    ///
+    /// **Note that this costs money.** A fee of 0.1 Telegram Stars per message
+    /// is withdrawn from the balance of the bot, for messages exceeding the
+    /// free limits.
+    ///
+    /// This parameter is **optional**, and defaults to `false`.
+    ///
+    /// [api]: https://core.telegram.org/bots/api#sendmessage
+    #[must_use]
+    pub fn allow_paid_broadcast(mut self, allowed: bool) -> Self {
+        self.options.allow_paid_broadcast = allowed;
+        self
+    }
+
+    /// Specifies a JSON object as the base of every `sendMessage` payload.
+    ///
+    /// This allows fields that are not otherwise exposed by the builder, e.g.
+    /// `protect_content`, or fields specific to a custom Bot API server. The
+    /// crate builds its fields on top of the template, so the following keys
+    /// are always overridden:
+    ///
+    /// - `chat_id`, `message_thread_id`, `reply_parameters` and
+    ///   `link_preview_options`
+    /// - `text` and `disable_notification`, on every send
+    /// - `parse_mode`, if a [parse mode] is specified or required by
+    ///   [`code_block_multiline`]
+    /// - `allow_paid_broadcast`, if [allowed]
+    ///
+    /// The template only applies to `sendMessage`. Other requests made by the
+    /// sink, e.g. for [deleting messages], are not affected.
+    ///
+    /// This parameter is **optional**.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use serde_json::{Map, json};
+    /// use spdlog_telegram::TelegramSink;
+    ///
+    /// TelegramSink::builder().payload_template(Map::from_iter([(
+    ///     "protect_content".into(),
+    ///     json!(true),
+    /// )]));
+    /// ```
+    ///
+    /// [parse mode]: TelegramSinkBuilder::parse_mode
+    /// [`code_block_multiline`]: TelegramSinkBuilder::code_block_multiline
+    /// [allowed]: TelegramSinkBuilder::allow_paid_broadcast
+    /// [deleting messages]: TelegramSinkBuilder::auto_delete
+    #[must_use]
+    pub fn payload_template(mut self, template: json::Map<String, json::Value>) -> Self {
+        self.options.payload_template = Some(template);
+        self
+    }
+
+    /// Specifies the maximum length of each key-value value.
+    ///
+    /// Values longer than this are truncated with an ellipsis in the `{kv}`
+    /// rendering, the length is measured in UTF-16 code units. This only
+    /// takes effect with the default formatter.
+    ///
+    /// This parameter is **optional**.
+    #[must_use]
+    pub fn max_kv_value_len(mut self, max_len: usize) -> Self {
+        if let Some(formatter) = &mut self.options.default_formatter {
+            formatter.max_kv_value_len = Some(max_len);
+        }
+        self
+    }
+
+    /// Specifies how key-values are rendered.
+    ///
+    /// This only takes effect with the default formatter.
+    ///
+    /// This parameter is **optional**, and defaults to [`KvStyle::Inline`].
+    #[must_use]
+    pub fn kv_style(mut self, style: KvStyle) -> Self {
+        if let Some(formatter) = &mut self.options.default_formatter {
+            formatter.kv_style = style;
+        }
+        self
+    }
+
+    /// Specifies the marker appended to truncated texts.
+    ///
+    /// It's used by all truncation, i.e. messages exceeding the length limit
+    /// and key-values exceeding [max_kv_value_len]. The marker counts against
+    /// the truncated length, and is omitted if it's longer than the length by
+    /// itself.
+    ///
+    /// This parameter is **optional**, and defaults to `"…"`.
+    ///
+    /// [max_kv_value_len]: TelegramSinkBuilder::max_kv_value_len
+    #[must_use]
+    pub fn ellipsis<S>(mut self, ellipsis: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.options.ellipsis = ellipsis.into();
+        if let Some(formatter) = &mut self.options.default_formatter {
+            formatter.ellipsis = self.options.ellipsis.clone();
+        }
+        self
+    }
+
+    // Prop
+    //
+
+    /// Specifies a prop, replacing the current one wholesale.
+    ///
+    /// This is for applications sharing a preconfigured [`SinkProp`] across
+    /// sinks. It overrides the level filter, formatter and error handler set
+    /// before, including the default formatter and its options. Setting them
+    /// afterwards modifies the given prop.
+    ///
+    /// This parameter is **optional**.
+    #[must_use]
+    pub fn prop(mut self, prop: SinkProp) -> Self {
+        self.options.prop = prop;
+        self.options.default_formatter = None;
+        self
+    }
+
+    /// Specifies a log level filter.
+    ///
+    /// This parameter is **optional**.
+    #[must_use]
+    pub fn level_filter(self, level_filter: LevelFilter) -> Self {
+        self.options.prop.set_level_filter(level_filter);
+        self
+    }
+
+    /// Specifies a formatter.
+    ///
+    /// This parameter is **optional**.
+    #[must_use]
+    pub fn formatter<F>(mut self, formatter: F) -> Self
+    where
+        F: Formatter + 'static,
+    {
+        self.options.prop.set_formatter(formatter);
+        self.options.default_formatter = None;
+        self
+    }
+
+    /// Specifies an error handler.
+    ///
+    /// This parameter is **optional**.
+    #[must_use]
+    pub fn error_handler<F>(self, handler: F) -> Self
+    where
+        F: Into<ErrorHandler>,
+    {
+        self.options.prop.set_error_handler(handler);
+        self
+    }
+}
+
+impl<ArgR> TelegramSinkBuilder<(), ArgR> {
+    #[doc(hidden)]
+    #[deprecated(note = "\n\n\
+        builder compile-time error:\n\
+        - missing required field `bot_token`\n\n\
+    ")]
+    pub fn build(self, _: Infallible) {}
+}
+
+impl TelegramSinkBuilder<String, ()> {
+    /// Builds a `TelegramSink` without a recipient, which is bound later with
+    /// [`TelegramSink::set_recipient`].
+    ///
+    /// This is an escape hatch for frameworks where the recipient is not known
+    /// when constructing loggers. Until a recipient is bound, logs are dropped
+    /// silently, they are neither buffered nor counted in [stats].
+    ///
+    /// [stats]: TelegramSink::stats
+    pub fn build_deferred(self) -> Result<TelegramSink> {
+        self.build_inner().map(|(sink, _)| sink)
+    }
+
+    #[doc(hidden)]
+    #[deprecated(note = "\n\n\
+        builder compile-time error:\n\
+        - missing required field `recipient`\n\n\
+    ")]
+    pub fn build(self, _: Infallible) {}
+}
+
+impl TelegramSinkBuilder<String, Recipient> {
+    /// Builds a `TelegramSink`, and sends a test message to the recipient to
+    /// confirm that delivery works.
+    ///
+    /// The message `"✅ spdlog-telegram connected"` is sent without
+    /// notification sound. Returns an error if the delivery fails, e.g. for an
+    /// invalid bot token, an invalid recipient or missing permissions, which
+    /// would otherwise only be noticed on the first log.
+    ///
+    /// Use [`TelegramSinkBuilder::build`] if no network operation is desired.
+    pub fn build_and_test(self) -> Result<TelegramSink> {
+        let (mut sink, report) = self.build_inner()?;
+        let sent = sink.requester.send_log(&Message {
+            text: "✅ spdlog-telegram connected".into(),
+            disable_notification: true,
+            parse_mode: None,
+            thread_id: None,
+            reply_to: None,
+            buttons: None,
+            level: None,
+        })?;
+        if let (Some(sent), Some(auto_delete)) = (sent, &sink.auto_delete) {
+            auto_delete.schedule(&sent);
+        }
+        sink.token_validated = Some(!report.dry_run);
+        Ok(sink)
+    }
+
+    /// Builds a `TelegramSink`, returning diagnostics of the effective
+    /// configuration along with it.
+    ///
+    /// See [`BuildReport`]. This is the same as [`TelegramSinkBuilder::build`]
+    /// otherwise, no extra network operations are involved.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use spdlog_telegram::TelegramSink;
+    ///
+    /// let (sink, report) = TelegramSink::builder()
+    ///     .bot_token("1234567890:AbCdEfGhiJkLmNoPq1R2s3T4u5V6w7X8y9z")
+    ///     .recipient("@my_channel")
+    ///     .build_with_report()?;
+    /// println!("telegram sink built: {report:?}");
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn build_with_report(self) -> Result<(TelegramSink, BuildReport)> {
+        self.build_inner()
+    }
+
+    /// Builds a `TelegramSink`.
+    pub fn build(self) -> Result<TelegramSink> {
+        self.build_inner().map(|(sink, _)| sink)
+    }
+}
+
+// Implemented by the states of the `recipient` field of the builder, so that
+// `build` and `build_deferred` share the implementation.
+trait RecipientArg {
+    fn into_recipient(self) -> Option<Recipient>;
+}
+
+impl RecipientArg for () {
+    fn into_recipient(self) -> Option<Recipient> {
+        None
+    }
+}
+
+impl RecipientArg for Recipient {
+    fn into_recipient(self) -> Option<Recipient> {
+        Some(self)
+    }
+}
+
+impl<ArgR> TelegramSinkBuilder<String, ArgR> {
+    fn build_inner(mut self) -> Result<(TelegramSink, BuildReport)>
+    where
+        ArgR: RecipientArg,
+    {
+        let mut endpoint_redacted = None;
+        let mut http2_prior_knowledge = false;
+        let transport: Box<dyn Transport> = match self.options.transport {
+            Some(transport) => transport,
+            None => {
+                // The official server doesn't support HTTP/2 without negotiation.
+                http2_prior_knowledge = self.options.http2_prior_knowledge
+                    && self.options.server_url.is_some()
+                    && self.options.http_client.is_none();
+                let options = ClientOptions {
+                    http2_prior_knowledge,
+                    tcp_nodelay: self.options.tcp_nodelay,
+                    local_address: self.options.local_address,
+                };
+                let mut transport = HttpTransport::new(
+                    self.options
+                        .server_url
+                        .map_or_else(|| Url::parse("https://api.telegram.org"), Ok)
+                        .map_err(Error::ParseUrl)?,
+                    &self.bot_token,
+                    self.options.http_client,
+                    self.options.content_type,
+                    options,
+                )?;
+                transport.set_ok_field(self.options.ok_field.clone());
+                transport.set_desensitize_errors(self.options.desensitize_errors);
+                transport.set_max_request_bytes(self.options.max_request_bytes);
+                endpoint_redacted = Some(transport.endpoint_redacted("sendMessage"));
+                Box::new(transport)
+            }
+        };
+        let mut requester = Requester::new(transport);
+        if let Some(template) = self.options.payload_template {
+            requester.set_payload_template(template);
+        }
+        requester.set_response_fields(self.options.ok_field, self.options.description_field);
+        requester.set_max_request_bytes(self.options.max_request_bytes);
+        requester.set_follow_chat_migration(self.options.follow_chat_migration);
+        requester.set_require_topic(self.options.require_topic);
+        requester.set_allow_sending_without_reply(self.options.allow_sending_without_reply);
+        if let Some(before_send) = self.options.before_send {
+            requester.set_before_send(before_send);
+        }
+        if let Some(on_latency) = self.options.on_latency {
+            requester.set_on_latency(on_latency);
+        }
+        if self.options.thread_by_key.is_some() {
+            requester.set_reply_threads(ReplyThreads::DEFAULT_CAPACITY);
+        }
+        if self.options.allow_paid_broadcast {
+            requester.set_allow_paid_broadcast();
+        }
+        let link_preview = self
+            .options
+            .link_preview
+            .unwrap_or(self.options.parse_mode.is_some());
+        requester.set_link_preview(link_preview, self.options.legacy_link_preview);
+        let dry_run = self.options.dry_run.is_some();
+        if let Some(writer) = self.options.dry_run {
+            requester.set_dry_run(writer);
+        }
+        let recipient = self.recipient.into_recipient();
+        let bound = recipient.is_some();
+        for warning in recipient
+            .iter()
+            .chain(
+                self.options
+                    .recipient_rules
+                    .iter()
+                    .map(|(_, recipient)| recipient),
+            )
+            .filter_map(|recipient| recipient.check_target(self.options.create_topic.is_some()))
+        {
+            self.options
+                .prop
+                .call_error_handler(spdlog::Error::Downstream(warning.into()));
+        }
+        // Any Bot API call made while preparing recipients validates the token.
+        let token_validated = (self.options.create_topic.is_some()
+            || recipient
+                .iter()
+                .chain(
+                    self.options
+                        .recipient_rules
+                        .iter()
+                        .map(|(_, recipient)| recipient),
+                )
+                .any(|recipient| recipient.linked_discussion))
+        .then_some(!dry_run);
+        if let Some(recipient) = recipient {
+            requester.bind(recipient, self.options.create_topic.as_ref())?;
+        }
+        for (level_filter, recipient) in self.options.recipient_rules {
+            requester.add_rule(level_filter, recipient, self.options.create_topic.as_ref())?;
+        }
+
+        if let Some(formatter) = &mut self.options.default_formatter {
+            formatter.source &= !self.options.source_as_reply;
+        }
+        let default_pattern = self
+            .options
+            .default_formatter
+            .as_ref()
+            .map(DefaultFormatter::pattern);
+        if let Some(mut formatter) = self.options.default_formatter {
+            formatter.html_level_badge &= self.options.parse_mode == Some(ParseMode::Html);
+            self.options.prop.set_formatter(formatter);
+        }
+
+        let report = BuildReport {
+            token_validated: token_validated == Some(true),
+            endpoint_redacted: endpoint_redacted.clone(),
+            default_pattern: default_pattern.clone(),
+            link_preview,
+            http2_prior_knowledge,
+            dry_run,
+        };
+        let prop = Arc::new(self.options.prop);
+        let requester = Arc::new(requester);
+        let auto_delete = self
+            .options
+            .auto_delete
+            .map(|ttl| AutoDeleter::new(ttl, requester.clone(), prop.clone()))
+            .transpose()
+            .map_err(Error::SpawnThread)?;
+        let sink = TelegramSink {
+            auto_delete,
+            prop,
+            silence: AtomicLevelFilter::new(self.options.silence),
+            silence_fn: ArcSwapOption::empty(),
+            force_notify: self.options.force_notify,
+            notify_rate_limit: self
+                .options
+                .notify_rate_limit
+                .map(|window| (window, Mutex::new(None))),
+            header: self
+                .options
+                .environment_badge
+                .map(|badge| format!("[{badge}]")),
+            footer_fields: self
+                .options
+                .source_tag
+                .map_or_else(Vec::new, |tag| tag.fields()),
+            started: self.options.include_uptime.then(Instant::now),
+            ellipsis: self.options.ellipsis,
+            on_empty: self.options.on_empty,
+            parse_mode: self.options.parse_mode,
+            parse_mode_key: self.options.parse_mode_key,
+            code_block_multiline: self.options.code_block_multiline,
+            collapse_long: self.options.collapse_long,
+            trim_output: self.options.trim_output,
+            retry_predicate: self.options.retry_predicate,
+            forward: self.options.forward,
+            reply_to_if: self.options.reply_to_if,
+            inline_buttons: self.options.inline_buttons,
+            raw_payload: self.options.raw_payload,
+            source_as_reply: self.options.source_as_reply,
+            thread_id_key: self.options.thread_id_key,
+            thread_by_key: self.options.thread_by_key,
+            on_blocked: self.options.on_blocked,
+            category_icons: self.options.category_icons,
+            default_pattern,
+            endpoint_redacted,
+            token_validated,
+            blocked: AtomicBool::new(false),
+            bound: AtomicBool::new(bound),
+            create_topic: self.options.create_topic,
+            daily_topic: self
+                .options
+                .daily_topic
+                .map(|(name_fmt, utc_offset)| DailyTopic::new(name_fmt, utc_offset)),
+            requester,
+            counters: SendCounters::default(),
+        };
+        Ok((sink, report))
+    }
 }
 
-impl TelegramSinkBuilder<String, ()> {
-    #[doc(hidden)]
-    #[deprecated(note = "\n\n\
-        builder compile-time error:\n\
-        - missing required field `recipient`\n\n\
-    ")]
-    pub fn build(self, _: Infallible) {}
-}
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicU64;
+
+    use mockito::Matcher;
+    use serde_json::{Map, json};
+    use spdlog::formatter::{PatternFormatter, pattern};
+
+    use super::*;
+
+    const BOT_TOKEN: &str = "1234567890:AbCdEfGhiJkLmNoPq1R2s3T4u5V6w7X8y9z";
+    const SEND_MESSAGE: &str = "/bot1234567890:AbCdEfGhiJkLmNoPq1R2s3T4u5V6w7X8y9z/sendMessage";
+
+    fn builder(server: &mockito::Server) -> TelegramSinkBuilder<String, Recipient> {
+        TelegramSink::builder()
+            .error_handler(|err| panic!("error handler triggered: {err}"))
+            .server_url(Url::parse(&server.url()).unwrap())
+            .bot_token(BOT_TOKEN)
+            .recipient(-1001234567890)
+            .formatter(PatternFormatter::new(pattern!("{payload}")))
+    }
+
+    // A writer for dry-run output
+    #[derive(Clone, Default)]
+    struct Output(Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl Output {
+        fn contents(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    impl Write for Output {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct Records {
+        prop: SinkProp,
+        records: std::sync::Mutex<Vec<spdlog::RecordOwned>>,
+    }
+
+    impl GetSinkProp for Records {
+        fn prop(&self) -> &SinkProp {
+            &self.prop
+        }
+    }
+
+    impl Sink for Records {
+        fn log(&self, record: &Record) -> spdlog::Result<()> {
+            self.records.lock().unwrap().push(record.to_owned());
+            Ok(())
+        }
+
+        fn flush(&self) -> spdlog::Result<()> {
+            Ok(())
+        }
+    }
+
+    // Captures the records of `log`, so that they can be rendered or formatted
+    // without a Bot API server. The logger is named `app`.
+    pub(crate) fn records(log: impl FnOnce(&Logger)) -> Vec<spdlog::RecordOwned> {
+        let records = Arc::new(Records::default());
+        log(&Logger::builder()
+            .name("app")
+            .sink(records.clone())
+            .level_filter(LevelFilter::All)
+            .build()
+            .unwrap());
+        Arc::into_inner(records)
+            .unwrap()
+            .records
+            .into_inner()
+            .unwrap()
+    }
+
+    fn render_builder() -> TelegramSinkBuilder<String, Recipient> {
+        TelegramSink::builder()
+            .error_handler(|err| panic!("error handler triggered: {err}"))
+            .bot_token(BOT_TOKEN)
+            .recipient(-1001234567890)
+            .formatter(PatternFormatter::new(pattern!("{payload}")))
+    }
+
+    // Renders the logs of `log` as the built sink would send them.
+    fn render_logs(
+        builder: TelegramSinkBuilder<String, Recipient>,
+        log: impl FnOnce(&Logger),
+    ) -> Vec<String> {
+        let sink = builder.build().unwrap();
+        records(log)
+            .iter()
+            .map(|record| sink.render(&record.as_ref()).unwrap().unwrap())
+            .collect()
+    }
+
+    fn build_logger(sink: TelegramSink) -> Logger {
+        Logger::builder()
+            .error_handler(|err| panic!("error handler triggered: {err}"))
+            .sink(Arc::new(sink))
+            .build()
+            .unwrap()
+    }
+
+    // Mocks a successful `sendMessage` with a payload containing `fields`.
+    fn mock_send(server: &mut mockito::Server, fields: json::Value) -> mockito::Mock {
+        server
+            .mock("POST", SEND_MESSAGE)
+            .match_body(Matcher::PartialJson(fields))
+            .with_body(json!({ "ok": true, "result": {} }).to_string())
+            .create()
+    }
+
+    fn mock_text(server: &mut mockito::Server, text: impl Into<String>) -> mockito::Mock {
+        mock_send(server, json!({ "text": text.into() }))
+    }
+
+    fn mock_silent(server: &mut mockito::Server, silent: bool) -> mockito::Mock {
+        mock_send(server, json!({ "disable_notification": silent }))
+    }
+
+    #[test]
+    fn request() {
+        let mut server = mockito::Server::new();
+
+        let error_handler = |err| panic!("error handler triggered: {err}");
+        let sink = Arc::new(
+            TelegramSink::builder()
+                .error_handler(error_handler)
+                .server_url(Url::parse(&server.url()).unwrap())
+                .bot_token("1234567890:AbCdEfGhiJkLmNoPq1R2s3T4u5V6w7X8y9z")
+                .recipient(
+                    Recipient::builder()
+                        .chat_id(-1001234567890)
+                        .thread_id(114)
+                        .reply_to(514)
+                        .build(),
+                )
+                .silence(LevelFilter::MoreVerboseEqual(Level::Info))
+                .build()
+                .unwrap(),
+        );
+        let logger = Logger::builder()
+            .error_handler(error_handler)
+            .sink(sink.clone())
+            .build()
+            .unwrap();
+
+        let mut mocker = |level| {
+            server
+                .mock(
+                    "POST",
+                    "/bot1234567890:AbCdEfGhiJkLmNoPq1R2s3T4u5V6w7X8y9z/sendMessage",
+                )
+                .match_header("content-type", "application/json")
+                .match_body(Matcher::PartialJson(json!({
+                    "chat_id": -1001234567890_i64,
+                    "disable_notification": sink.silence().test(level),
+                    "link_preview_options": {
+                        "is_disabled": true
+                    },
+                    "message_thread_id": 114,
+                    "text": format!("#log #{} Hello Telegram! k=v", level.as_str()),
+                    "reply_parameters": {
+                        "message_id": 514,
+                    }
+                })))
+                .with_header("content-type", "application/json")
+                .with_body(json!({ "ok": true, "result": { /* omitted */ }}).to_string())
+                .create()
+        };
+
+        let mock = mocker(Level::Info);
+        info!(logger: logger, "Hello Telegram!", kv: { k = "v" });
+        mock.assert();
+
+        let mock = mocker(Level::Error);
+        error!(logger: logger, "Hello Telegram!", kv: { k = "v" });
+        mock.assert();
+    }
+
+    #[test]
+    fn source_tag() {
+        let footer = format!("[pid={} region=eu]", std::process::id());
+        let long = "x".repeat(text::MAX_TEXT_LEN);
+        let rendered = render_logs(
+            render_builder().source_tag(SourceTag {
+                hostname: false,
+                pid: true,
+                custom: Some("region=eu".into()),
+            }),
+            |logger| {
+                info!(logger: logger, "Hello Telegram!");
+                info!(logger: logger, "{long}");
+            },
+        );
+
+        let mut truncated = long.clone();
+        text::truncate_utf16(
+            &mut truncated,
+            text::MAX_TEXT_LEN - footer.len() - 1,
+            text::ELLIPSIS,
+        );
+        assert_eq!(
+            rendered,
+            [
+                format!("Hello Telegram!\n{footer}"),
+                format!("{truncated}\n{footer}")
+            ]
+        );
+    }
+
+    #[test]
+    fn ellipsis() {
+        let long = "x".repeat(text::MAX_TEXT_LEN);
+        let rendered = render_logs(
+            TelegramSink::builder()
+                .bot_token(BOT_TOKEN)
+                .recipient(-1001234567890)
+                .log_tag(None)
+                .max_kv_value_len(8)
+                .ellipsis("...")
+                .source_tag(SourceTag {
+                    custom: Some("region=eu".into()),
+                    ..Default::default()
+                }),
+            |logger| {
+                info!(logger: logger, "a", kv: { k = "abcdefghijk" });
+                info!(logger: logger, "{long}");
+            },
+        );
+
+        let mut truncated = format!("#info {long} ");
+        text::truncate_utf16(&mut truncated, text::MAX_TEXT_LEN - 12, "...");
+        assert!(truncated.ends_with("x..."));
+        assert_eq!(
+            rendered,
+            [
+                "#info a k=abcde...\n[region=eu]".to_string(),
+                format!("{truncated}\n[region=eu]")
+            ]
+        );
+    }
+
+    #[test]
+    fn truncate_without_decorations() {
+        let long = "x".repeat(text::MAX_TEXT_LEN + 1);
+        let rendered = render_logs(render_builder(), |logger| {
+            info!(logger: logger, "{}", &long[..text::MAX_TEXT_LEN]);
+            info!(logger: logger, "{long}");
+        });
+        assert_eq!(rendered[0], long[..text::MAX_TEXT_LEN]);
+        assert_eq!(rendered[1], format!("{}…", &long[..text::MAX_TEXT_LEN - 1]));
+
+        let rendered = render_logs(render_builder().ellipsis("..."), |logger| {
+            info!(logger: logger, "{long}");
+        });
+        assert_eq!(
+            rendered[0],
+            format!("{}...", &long[..text::MAX_TEXT_LEN - 3])
+        );
+    }
+
+    #[test]
+    fn html_level_badge() {
+        let render = |parse_mode| {
+            let builder = TelegramSink::builder()
+                .bot_token(BOT_TOKEN)
+                .recipient(-1001234567890)
+                .html_level_badge(true);
+            let builder = match parse_mode {
+                Some(parse_mode) => builder.parse_mode(parse_mode),
+                None => builder,
+            };
+            render_logs(builder, |logger| {
+                error!(logger: logger, "a < b", kv: { k = "v" });
+            })
+        };
+        assert_eq!(
+            render(Some(ParseMode::Html)),
+            ["#log <b>🔴 ERROR</b> a &lt; b k=v"]
+        );
+        // No effect for other parse modes
+        for parse_mode in [None, Some(ParseMode::MarkdownV2)] {
+            assert_eq!(render(parse_mode), ["#log #error a < b k=v"]);
+        }
+    }
+
+    #[test]
+    fn recipient_for() {
+        let mut server = mockito::Server::new();
+        let logger = build_logger(
+            builder(&server)
+                .recipient_for(
+                    LevelFilter::MoreSevereEqual(Level::Error),
+                    Recipient::builder().username("@pager").thread_id(7).build(),
+                )
+                .recipient_for(LevelFilter::MoreSevereEqual(Level::Warn), "@alerts")
+                // Never matches, as the first rule wins
+                .recipient_for(LevelFilter::MoreSevereEqual(Level::Critical), "@unused")
+                .build()
+                .unwrap(),
+        );
+
+        let mut mock_chat = |chat_id: json::Value, thread_id: json::Value, text: &str| {
+            mock_send(
+                &mut server,
+                json!({
+                    "chat_id": chat_id,
+                    "message_thread_id": thread_id,
+                    "text": text,
+                }),
+            )
+        };
+        let pager = mock_chat("@pager".into(), 7.into(), "fatal");
+        let critical = mock_chat("@pager".into(), 7.into(), "oops");
+        let alerts = mock_chat("@alerts".into(), json::Value::Null, "disk");
+        let default = mock_chat((-1001234567890i64).into(), json::Value::Null, "hello");
+        critical!(logger: logger, "fatal");
+        error!(logger: logger, "oops");
+        warn!(logger: logger, "disk");
+        info!(logger: logger, "hello");
+        pager.assert();
+        critical.assert();
+        alerts.assert();
+        default.assert();
+    }
+
+    #[test]
+    fn prop() {
+        let mut server = mockito::Server::new();
+        let prop = SinkProp::default();
+        prop.set_level_filter(LevelFilter::MoreSevereEqual(Level::Warn));
+        prop.set_formatter(PatternFormatter::new(pattern!("{payload}")));
+        let logger = build_logger(
+            builder(&server)
+                .level_filter(LevelFilter::All)
+                .prop(prop)
+                .build()
+                .unwrap(),
+        );
+
+        let mock = mock_text(&mut server, "shared").expect(1);
+        info!(logger: logger, "filtered");
+        warn!(logger: logger, "shared");
+        mock.assert();
+    }
+
+    #[test]
+    fn collapse_long() {
+        let mut server = mockito::Server::new();
+        let logger = build_logger(
+            builder(&server)
+                .collapse_long(10)
+                .code_block_multiline(true)
+                .build()
+                .unwrap(),
+        );
+
+        let mock = mock_send(
+            &mut server,
+            json!({
+                "text": "<blockquote expandable>panicked at a &lt; b\nbacktrace</blockquote>",
+                "parse_mode": "HTML",
+            }),
+        );
+        error!(logger: logger, "panicked at a < b\nbacktrace");
+        mock.assert();
+
+        // Short texts are not collapsed
+        let mock = mock_text(&mut server, "<pre>a\nb</pre>");
+        error!(logger: logger, "a\nb");
+        mock.assert();
+
+        let logger = build_logger(
+            builder(&server)
+                .parse_mode(ParseMode::MarkdownV2)
+                .collapse_long(10)
+                .build()
+                .unwrap(),
+        );
+        let mock = mock_send(
+            &mut server,
+            json!({
+                "text": "**>panicked at *main*\n>backtrace||",
+                "parse_mode": "MarkdownV2",
+            }),
+        );
+        error!(logger: logger, "panicked at *main*\nbacktrace");
+        mock.assert();
+    }
+
+    #[test]
+    fn include_uptime() {
+        let log = |logger: &Logger| error!(logger: logger, "Hello Telegram!");
+        let rendered = render_logs(
+            render_builder()
+                .source_tag(SourceTag {
+                    custom: Some("region=eu".into()),
+                    ..Default::default()
+                })
+                .include_uptime(true),
+            log,
+        );
+        assert_eq!(rendered, ["Hello Telegram!\n[region=eu uptime=0s]"]);
+
+        let rendered = render_logs(render_builder().include_uptime(true), log);
+        assert_eq!(rendered, ["Hello Telegram!\n[uptime=0s]"]);
+    }
+
+    #[test]
+    fn silence_fn() {
+        let mut server = mockito::Server::new();
+        let sink = Arc::new(builder(&server).silence(LevelFilter::All).build().unwrap());
+        let logger = Logger::builder().sink(sink.clone()).build().unwrap();
+
+        let mock = mock_silent(&mut server, true);
+        info!(logger: logger, "Hello Telegram!");
+        mock.assert();
+
+        sink.set_silence_fn(Arc::new(|record| record.payload().contains("quiet")));
+        let mock = mock_silent(&mut server, false);
+        info!(logger: logger, "Hello Telegram!");
+        mock.assert();
+        let mock = mock_silent(&mut server, true);
+        info!(logger: logger, "Hello quiet Telegram!");
+        mock.assert();
+
+        sink.clear_silence_fn();
+        let mock = mock_silent(&mut server, true);
+        info!(logger: logger, "Hello Telegram!");
+        mock.assert();
+    }
+
+    #[test]
+    fn force_notify() {
+        let mut server = mockito::Server::new();
+        let sink = Arc::new(
+            builder(&server)
+                .silence(LevelFilter::MoreVerboseEqual(Level::Error))
+                .force_notify(LevelFilter::MoreSevereEqual(Level::Error))
+                .build()
+                .unwrap(),
+        );
+        let logger = Logger::builder().sink(sink.clone()).build().unwrap();
+
+        // Both filters match `Error`, the force-notify filter wins
+        for (level, silent) in [
+            (Level::Info, true),
+            (Level::Error, false),
+            (Level::Critical, false),
+        ] {
+            let mock = mock_silent(&mut server, silent);
+            log!(logger: logger, level, "Hello Telegram!");
+            mock.assert();
+        }
+
+        // It also takes precedence over the silence predicate
+        sink.set_silence_fn(Arc::new(|_| true));
+        let mock = mock_silent(&mut server, false);
+        error!(logger: logger, "Hello Telegram!");
+        mock.assert();
+        let mock = mock_silent(&mut server, true);
+        warn!(logger: logger, "Hello Telegram!");
+        mock.assert();
+    }
+
+    #[test]
+    fn notify_rate_limit() {
+        let mut server = mockito::Server::new();
+        let logger = build_logger(
+            builder(&server)
+                .silence(LevelFilter::MoreVerboseEqual(Level::Info))
+                .notify_rate_limit(Duration::from_millis(300))
+                .build()
+                .unwrap(),
+        );
+
+        for (level, silent) in [
+            (Level::Error, false),
+            (Level::Error, true),
+            // Silent messages don't start the window
+            (Level::Info, true),
+            (Level::Critical, true),
+        ] {
+            let mock = mock_silent(&mut server, silent);
+            log!(logger: logger, level, "Hello Telegram!");
+            mock.assert();
+        }
+
+        thread::sleep(Duration::from_millis(300));
+        for silent in [false, true] {
+            let mock = mock_silent(&mut server, silent);
+            error!(logger: logger, "Hello Telegram!");
+            mock.assert();
+        }
+    }
+
+    #[test]
+    fn always_silent_audible() {
+        let mut server = mockito::Server::new();
+        let sink = Arc::new(builder(&server).always_silent().build().unwrap());
+        let logger = Logger::builder().sink(sink.clone()).build().unwrap();
+
+        let mock = mock_silent(&mut server, true);
+        critical!(logger: logger, "Hello Telegram!");
+        mock.assert();
+
+        sink.set_always_audible();
+        assert_eq!(sink.silence(), LevelFilter::Off);
+        let mock = mock_silent(&mut server, false);
+        info!(logger: logger, "Hello Telegram!");
+        mock.assert();
+
+        sink.set_always_silent();
+        assert_eq!(sink.silence(), LevelFilter::All);
+    }
+
+    #[test]
+    fn content_type() {
+        let mut server = mockito::Server::new();
+        let with_charset = build_logger(
+            builder(&server)
+                .content_type(Some("application/json; charset=utf-8"))
+                .build()
+                .unwrap(),
+        );
+        let without = build_logger(builder(&server).content_type(None).build().unwrap());
+
+        let mut mocker = |content_type| {
+            server
+                .mock("POST", SEND_MESSAGE)
+                .match_header("content-type", content_type)
+                .with_body(json!({ "ok": true, "result": {} }).to_string())
+                .create()
+        };
+
+        let mock = mocker(Matcher::Exact("application/json; charset=utf-8".into()));
+        info!(logger: with_charset, "Hello Telegram!");
+        mock.assert();
+
+        let mock = mocker(Matcher::Missing);
+        info!(logger: without, "Hello Telegram!");
+        mock.assert();
+    }
+
+    #[test]
+    fn create_topic() {
+        let mut server = mockito::Server::new();
+
+        let create = server
+            .mock(
+                "POST",
+                "/bot1234567890:AbCdEfGhiJkLmNoPq1R2s3T4u5V6w7X8y9z/createForumTopic",
+            )
+            .match_body(Matcher::Json(json!({
+                "chat_id": -1001234567890_i64,
+                "name": "incident-42",
+                "icon_color": 0xFB6F5F,
+            })))
+            .with_body(json!({ "ok": true, "result": { "message_thread_id": 1919 } }).to_string())
+            .create();
+        let logger = build_logger(
+            builder(&server)
+                .create_topic("incident-42", Some(TopicIconColor::Red))
+                .build()
+                .unwrap(),
+        );
+        create.assert();
+
+        let mock = mock_send(&mut server, json!({ "message_thread_id": 1919 }));
+        info!(logger: logger, "Hello Telegram!");
+        mock.assert();
+
+        server
+            .mock(
+                "POST",
+                "/bot1234567890:AbCdEfGhiJkLmNoPq1R2s3T4u5V6w7X8y9z/createForumTopic",
+            )
+            .with_status(400)
+            .with_body(
+                json!({ "ok": false, "description": "Bad Request: not enough rights" }).to_string(),
+            )
+            .create();
+        assert!(matches!(
+            builder(&server).create_topic("incident-43", None).build(),
+            Err(Error::TelegramApi {
+                description: Some(_),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn build_deferred() {
+        let mut server = mockito::Server::new();
+        let mock = server.mock("POST", Matcher::Any).expect(0).create();
+        let sink = Arc::new(
+            TelegramSink::builder()
+                .error_handler(|err| panic!("error handler triggered: {err}"))
+                .server_url(Url::parse(&server.url()).unwrap())
+                .bot_token(BOT_TOKEN)
+                .formatter(PatternFormatter::new(pattern!("{payload}")))
+                .build_deferred()
+                .unwrap(),
+        );
+        let logger = Logger::builder().sink(sink.clone()).build().unwrap();
+        info!(logger: logger, "dropped");
+        mock.assert();
+        assert_eq!(sink.stats().total_delivered(), 0);
+        drop(mock);
+
+        sink.set_recipient(-1001234567890).unwrap();
+        let mock = mock_send(
+            &mut server,
+            json!({
+                "chat_id": -1001234567890_i64,
+                "text": "Hello Telegram!",
+            }),
+        );
+        info!(logger: logger, "Hello Telegram!");
+        mock.assert();
+    }
+
+    #[test]
+    fn set_recipient() {
+        let mut server = mockito::Server::new();
+        let create = |server: &mut mockito::Server, chat_id: i64, thread_id: u64| {
+            server
+                .mock(
+                    "POST",
+                    "/bot1234567890:AbCdEfGhiJkLmNoPq1R2s3T4u5V6w7X8y9z/createForumTopic",
+                )
+                .match_body(Matcher::PartialJson(json!({ "chat_id": chat_id })))
+                .with_body(
+                    json!({ "ok": true, "result": { "message_thread_id": thread_id } }).to_string(),
+                )
+                .create()
+        };
+        let created = create(&mut server, -1001234567890, 1919);
+        let sink = Arc::new(
+            builder(&server)
+                .recipient(
+                    Recipient::builder()
+                        .chat_id(-1001234567890)
+                        .reply_to(514)
+                        .build(),
+                )
+                .create_topic("logs", None)
+                .build()
+                .unwrap(),
+        );
+        created.assert();
+
+        // The topic is created again in the new chat, and the reply is removed
+        let created = create(&mut server, -1009876543210, 810);
+        sink.set_recipient(-1009876543210).unwrap();
+        created.assert();
+
+        let logger = Logger::builder().sink(sink.clone()).build().unwrap();
+        let mock = server
+            .mock("POST", SEND_MESSAGE)
+            .match_body(Matcher::Json(json!({
+                "chat_id": -1009876543210_i64,
+                "message_thread_id": 810,
+                "text": "Hello Telegram!",
+                "link_preview_options": { "is_disabled": true },
+                "disable_notification": false,
+            })))
+            .with_body(json!({ "ok": true, "result": {} }).to_string())
+            .create();
+        info!(logger: logger, "Hello Telegram!");
+        mock.assert();
+    }
+
+    #[test]
+    fn linked_discussion() {
+        let mut server = mockito::Server::new();
+
+        let get_chat = server
+            .mock(
+                "POST",
+                "/bot1234567890:AbCdEfGhiJkLmNoPq1R2s3T4u5V6w7X8y9z/getChat",
+            )
+            .match_body(Matcher::Json(json!({ "chat_id": -1001234567890_i64 })))
+            .with_body(
+                json!({
+                    "ok": true,
+                    "result": { "id": -1001234567890_i64, "linked_chat_id": -1009876543210_i64 },
+                })
+                .to_string(),
+            )
+            .create();
+        let logger = build_logger(
+            builder(&server)
+                .recipient(Recipient::linked_discussion(-1001234567890))
+                .build()
+                .unwrap(),
+        );
+        get_chat.assert();
+
+        let mock = mock_send(&mut server, json!({ "chat_id": -1009876543210_i64 }));
+        info!(logger: logger, "Hello Telegram!");
+        mock.assert();
+
+        server
+            .mock(
+                "POST",
+                "/bot1234567890:AbCdEfGhiJkLmNoPq1R2s3T4u5V6w7X8y9z/getChat",
+            )
+            .with_body(json!({ "ok": true, "result": { "id": -1001111111111_i64 } }).to_string())
+            .create();
+        assert!(matches!(
+            builder(&server)
+                .recipient(Recipient::linked_discussion(-1001111111111))
+                .build(),
+            Err(Error::NoLinkedDiscussion)
+        ));
+    }
+
+    #[test]
+    fn user_id_as_channel() {
+        static WARNINGS: AtomicU64 = AtomicU64::new(0);
+
+        let server = mockito::Server::new();
+        let builder = |recipient| {
+            builder(&server)
+                .recipient(recipient)
+                .error_handler(|err| {
+                    assert!(matches!(
+                        err,
+                        spdlog::Error::Downstream(err) if matches!(
+                            err.downcast_ref(),
+                            Some(recipient::UserIdAsChannel { chat_id: 123456789 })
+                        )
+                    ));
+                    WARNINGS.fetch_add(1, Ordering::Relaxed);
+                })
+                .dry_run_writer(io::sink())
+        };
+
+        builder(Recipient::user_id(123456789).unwrap())
+            .build()
+            .unwrap();
+        builder(Recipient::linked_discussion(-1001234567890))
+            .build()
+            .unwrap();
+        assert_eq!(WARNINGS.load(Ordering::Relaxed), 0);
+
+        builder(Recipient::linked_discussion(123456789))
+            .build()
+            .unwrap();
+        assert_eq!(WARNINGS.load(Ordering::Relaxed), 1);
+        builder(Recipient::user_id(123456789).unwrap())
+            .create_topic("logs", None)
+            .build()
+            .unwrap();
+        assert_eq!(WARNINGS.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn require_topic() {
+        let mut server = mockito::Server::new();
+        let get_chat = |server: &mut mockito::Server, is_forum: bool| {
+            server
+                .mock("POST", Matcher::Regex("/getChat$".into()))
+                .match_body(Matcher::Json(json!({ "chat_id": -1001234567890_i64 })))
+                .with_body(
+                    json!({ "ok": true, "result": { "id": -1001234567890_i64, "is_forum": is_forum } })
+                        .to_string(),
+                )
+                .create()
+        };
+
+        let mock = get_chat(&mut server, true);
+        assert!(matches!(
+            builder(&server).require_topic(true).build(),
+            Err(Error::TopicRequired)
+        ));
+        mock.assert();
+        mock.remove();
+
+        // No `getChat` call if a thread ID is configured
+        let mock = server
+            .mock("POST", Matcher::Regex("/getChat$".into()))
+            .expect(0)
+            .create();
+        builder(&server)
+            .recipient((-1001234567890, 114))
+            .require_topic(true)
+            .build()
+            .unwrap();
+        mock.assert();
+        mock.remove();
+
+        let mock = get_chat(&mut server, false);
+        builder(&server).require_topic(true).build().unwrap();
+        mock.assert();
+    }
+
+    #[test]
+    fn daily_topic() {
+        static ERRORS: AtomicU64 = AtomicU64::new(0);
+
+        let mut server = mockito::Server::new();
+        let mut sink = builder(&server)
+            .daily_topic("logs {date}", 0)
+            .error_handler(|err: spdlog::Error| {
+                assert!(err.to_string().contains("not enough rights"));
+                ERRORS.fetch_add(1, Ordering::Relaxed);
+            })
+            .build()
+            .unwrap();
+        sink.daily_topic
+            .as_mut()
+            .unwrap()
+            .set_retry_delay(Duration::from_millis(200));
+        let logger = build_logger(sink);
+
+        // Falls back to the thread of the recipient, and isn't retried for a while
+        let create = server
+            .mock("POST", Matcher::Regex("/createForumTopic$".into()))
+            .with_body(
+                json!({ "ok": false, "description": "Bad Request: not enough rights" }).to_string(),
+            )
+            .expect(1)
+            .create();
+        let mock = mock_send(&mut server, json!({ "message_thread_id": null })).expect(2);
+        info!(logger: logger, "Hello Telegram!");
+        info!(logger: logger, "Hello Telegram!");
+        create.assert();
+        mock.assert();
+        assert_eq!(ERRORS.load(Ordering::Relaxed), 1);
+        create.remove();
+        mock.remove();
+        thread::sleep(Duration::from_millis(200));
+
+        // Created once for the day
+        let create = server
+            .mock("POST", Matcher::Regex("/createForumTopic$".into()))
+            .match_body(Matcher::Regex(r#""name":"logs \d{4}-\d{2}-\d{2}""#.into()))
+            .with_body(json!({ "ok": true, "result": { "message_thread_id": 7 } }).to_string())
+            .expect(1)
+            .create();
+        let mock = mock_send(&mut server, json!({ "message_thread_id": 7 })).expect(2);
+        info!(logger: logger, "Hello Telegram!");
+        info!(logger: logger, "Hello Telegram!");
+        create.assert();
+        mock.assert();
+    }
+
+    #[cfg(feature = "multi-thread")]
+    #[test]
+    fn async_pool() {
+        let mut server = mockito::Server::new();
+        let sink = builder(&server).build().unwrap().into_async_pool().unwrap();
+        let logger = Logger::builder()
+            .error_handler(|err| panic!("error handler triggered: {err}"))
+            .sink(Arc::new(sink))
+            .build()
+            .unwrap();
+
+        let mock = mock_text(&mut server, "Hello Telegram!");
+        info!(logger: logger, "Hello Telegram!");
+        // The default thread pool is destroyed with its last sink, which waits for
+        // pending operations.
+        drop(logger);
+        mock.assert();
+    }
+
+    #[cfg(feature = "multi-thread")]
+    #[test]
+    fn async_pool_level_filter() {
+        let mut server = mockito::Server::new();
+        let sink = builder(&server)
+            .level_filter(LevelFilter::MoreSevereEqual(Level::Error))
+            .build()
+            .unwrap()
+            .into_async_pool()
+            .unwrap();
+        let logger = Logger::builder()
+            .error_handler(|err| panic!("error handler triggered: {err}"))
+            .sink(Arc::new(sink))
+            .build()
+            .unwrap();
+
+        let mock = server.mock("POST", SEND_MESSAGE).expect(0).create();
+        info!(logger: logger, "Hello Telegram!");
+        drop(logger);
+        mock.assert();
+    }
+
+    #[test]
+    fn on_empty() {
+        static ERROR_HANDLED: AtomicBool = AtomicBool::new(false);
+
+        let mut server = mockito::Server::new();
+        let builder = |on_empty| builder(&server).on_empty(on_empty).build().unwrap();
+        let skip = build_logger(builder(EmptyPolicy::Skip));
+        let placeholder = build_logger(builder(EmptyPolicy::Placeholder("(empty)".into())));
+        let error = Logger::builder()
+            .sink(Arc::new(builder(EmptyPolicy::Error)))
+            .error_handler(|err| {
+                assert!(matches!(
+                    err,
+                    spdlog::Error::Downstream(err) if matches!(err.downcast_ref(), Some(Error::EmptyText))
+                ));
+                ERROR_HANDLED.store(true, Ordering::Relaxed);
+            })
+            .build()
+            .unwrap();
+
+        let mock = server.mock("POST", SEND_MESSAGE).expect(0).create();
+        info!(logger: skip, " \n\t ");
+        info!(logger: error, " \n\t ");
+        mock.assert();
+        assert!(ERROR_HANDLED.load(Ordering::Relaxed));
+
+        let mock = mock_text(&mut server, "(empty)");
+        info!(logger: placeholder, " \n\t ");
+        mock.assert();
+    }
+
+    #[test]
+    fn log_tag() {
+        let render = |tag| {
+            render_logs(
+                TelegramSink::builder_with_source(false)
+                    .bot_token(BOT_TOKEN)
+                    .recipient(-1001234567890)
+                    .log_tag(tag),
+                |logger| warn!(logger: logger, "Hello Telegram!"),
+            )
+        };
+        assert_eq!(render(Some("#myapp")), ["#myapp #warn Hello Telegram!"]);
+        assert_eq!(render(None), ["#warn Hello Telegram!"]);
+    }
+
+    #[test]
+    fn stats() {
+        let mut server = mockito::Server::new();
+        let sink = Arc::new(builder(&server).build().unwrap());
+        let logger = Logger::builder()
+            .sink(sink.clone())
+            .error_handler(|_| {})
+            .build()
+            .unwrap();
+
+        let mock = server
+            .mock("POST", SEND_MESSAGE)
+            .with_body(json!({ "ok": true, "result": {} }).to_string())
+            .expect(3)
+            .create();
+        info!(logger: logger, "Hello Telegram!");
+        info!(logger: logger, "Hello Telegram!");
+        error!(logger: logger, "Hello Telegram!");
+        mock.assert();
+        drop(mock);
+        server
+            .mock("POST", SEND_MESSAGE)
+            .with_status(500)
+            .with_body(json!({ "ok": false }).to_string())
+            .create();
+        error!(logger: logger, "Goodbye Telegram!");
+
+        let stats = sink.stats();
+        assert_eq!(stats.delivered(Level::Info), 2);
+        assert_eq!(stats.delivered(Level::Error), 1);
+        assert_eq!(stats.delivered(Level::Warn), 0);
+        assert_eq!(stats.total_delivered(), 3);
+        assert_eq!(stats.failures(), 1);
+
+        sink.reset_stats();
+        assert_eq!(sink.stats(), SendStats::default());
+    }
+
+    #[test]
+    fn html_escaped() {
+        let mut server = mockito::Server::new();
+        let logger = build_logger(
+            builder(&server)
+                .parse_mode(ParseMode::Html)
+                .formatter(HtmlEscaped::new(PatternFormatter::new(pattern!(
+                    "{payload}"
+                ))))
+                .build()
+                .unwrap(),
+        );
+
+        let mock = mock_send(
+            &mut server,
+            json!({
+                "text": "Vec&lt;u8&gt; &amp; &amp;str",
+                "parse_mode": "HTML",
+            }),
+        );
+        info!(logger: logger, "Vec<u8> & &str");
+        mock.assert();
+    }
+
+    #[test]
+    fn code_block_multiline() {
+        let mut server = mockito::Server::new();
+        let logger = build_logger(
+            builder(&server)
+                .code_block_multiline(true)
+                .source_tag(SourceTag {
+                    custom: Some("a<b".into()),
+                    ..Default::default()
+                })
+                .build()
+                .unwrap(),
+        );
+
+        let mock = mock_send(
+            &mut server,
+            json!({
+                "text": "<pre>thread 'main' panicked:\n1 &lt; 2</pre>\n[a&lt;b]",
+                "parse_mode": "HTML",
+            }),
+        );
+        error!(logger: logger, "thread 'main' panicked:\n1 < 2");
+        mock.assert();
+
+        // Single line texts are not wrapped
+        let mock = mock_send(&mut server, json!({ "text": "1 < 2\n[a<b]" }));
+        error!(logger: logger, "1 < 2");
+        mock.assert();
+    }
+
+    #[test]
+    fn dry_run() {
+        let mut server = mockito::Server::new();
+        let mock = server.mock("POST", Matcher::Any).expect(0).create();
+
+        let output = Output::default();
+        let logger = build_logger(
+            builder(&server)
+                .dry_run_writer(output.clone())
+                .create_topic("logs", None)
+                .build()
+                .unwrap(),
+        );
+        info!(logger: logger, "hello");
+        mock.assert();
+
+        let output = output.contents();
+        let lines = output.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("[spdlog-telegram dry-run] createForumTopic {"));
+        let (method, payload) = lines[1]
+            .strip_prefix("[spdlog-telegram dry-run] ")
+            .unwrap()
+            .split_once(' ')
+            .unwrap();
+        assert_eq!(method, "sendMessage");
+        let payload = serde_json::from_str::<serde_json::Value>(payload).unwrap();
+        assert_eq!(payload["text"], "hello");
+        assert_eq!(payload["chat_id"], -1001234567890i64);
+        assert!(!output.contains(BOT_TOKEN));
+    }
+
+    #[test]
+    fn retry_predicate() {
+        let mut server = mockito::Server::new();
+        let sink = Arc::new(
+            builder(&server)
+                .retry_predicate(|err, attempt| {
+                    assert!(matches!(err, Error::TelegramApi { .. }));
+                    (attempt < 3).then_some(Duration::ZERO)
+                })
+                .build()
+                .unwrap(),
+        );
+        let logger = Logger::builder()
+            .sink(sink.clone())
+            .error_handler(|_| {})
+            .build()
+            .unwrap();
+
+        // Succeeds at the 3rd attempt
+        let failure = server
+            .mock("POST", SEND_MESSAGE)
+            .with_status(500)
+            .with_body(json!({ "ok": false }).to_string())
+            .expect(2)
+            .create();
+        let success = server
+            .mock("POST", SEND_MESSAGE)
+            .with_body(json!({ "ok": true, "result": {} }).to_string())
+            .create();
+        info!(logger: logger, "Hello Telegram!");
+        failure.assert();
+        success.assert();
+        assert_eq!(sink.stats().total_delivered(), 1);
+        assert_eq!(sink.stats().retries(), 2);
+
+        // Gives up after the 3rd attempt
+        drop((failure, success));
+        let failure = server
+            .mock("POST", SEND_MESSAGE)
+            .with_status(500)
+            .with_body(json!({ "ok": false }).to_string())
+            .expect(3)
+            .create();
+        info!(logger: logger, "Hello Telegram!");
+        failure.assert();
+        assert_eq!(sink.stats().failures(), 1);
+        assert_eq!(sink.stats().retries(), 4);
+    }
+
+    #[test]
+    fn forward_on() {
+        let mut server = mockito::Server::new();
+        let logger = build_logger(
+            builder(&server)
+                .forward_on(
+                    LevelFilter::MoreSevereEqual(Level::Error),
+                    -1009876543210,
+                    42,
+                )
+                .build()
+                .unwrap(),
+        );
+
+        let send = server
+            .mock("POST", SEND_MESSAGE)
+            .with_body(json!({ "ok": true, "result": {} }).to_string())
+            .expect(2)
+            .create();
+        let forward = server
+            .mock(
+                "POST",
+                "/bot1234567890:AbCdEfGhiJkLmNoPq1R2s3T4u5V6w7X8y9z/forwardMessage",
+            )
+            .match_body(Matcher::PartialJson(json!({
+                "chat_id": -1001234567890i64,
+                "from_chat_id": -1009876543210i64,
+                "message_id": 42,
+            })))
+            .with_body(json!({ "ok": true, "result": {} }).to_string())
+            .create();
+        info!(logger: logger, "Hello Telegram!");
+        error!(logger: logger, "Hello Telegram!");
+        send.assert();
+        forward.assert();
+    }
+
+    #[test]
+    fn forward_on_notify_rate_limit() {
+        let mut server = mockito::Server::new();
+        let logger = build_logger(
+            builder(&server)
+                .forward_on(LevelFilter::All, -1009876543210, 42)
+                .notify_rate_limit(Duration::from_secs(3600))
+                .build()
+                .unwrap(),
+        );
+
+        let mut mock = |method, silent, hits| {
+            server
+                .mock(
+                    "POST",
+                    format!("/bot1234567890:AbCdEfGhiJkLmNoPq1R2s3T4u5V6w7X8y9z/{method}").as_str(),
+                )
+                .match_body(Matcher::PartialJson(
+                    json!({ "disable_notification": silent }),
+                ))
+                .with_body(json!({ "ok": true, "result": {} }).to_string())
+                .expect(hits)
+                .create()
+        };
+        // Both messages of the first log sound, and both of the second are muted.
+        let mocks = [
+            mock("sendMessage", false, 1),
+            mock("forwardMessage", false, 1),
+            mock("sendMessage", true, 1),
+            mock("forwardMessage", true, 1),
+        ];
+        error!(logger: logger, "first");
+        error!(logger: logger, "second");
+        for mock in mocks {
+            mock.assert();
+        }
+    }
+
+    #[test]
+    fn server_url_str() {
+        let mut server = mockito::Server::new();
+        let logger = build_logger(
+            TelegramSink::builder()
+                .error_handler(|err| panic!("error handler triggered: {err}"))
+                .server_url_str(&server.url())
+                .unwrap()
+                .bot_token(BOT_TOKEN)
+                .recipient(-1001234567890)
+                .formatter(PatternFormatter::new(pattern!("{payload}")))
+                .build()
+                .unwrap(),
+        );
+        let mock = mock_text(&mut server, "Hello Telegram!");
+        info!(logger: logger, "Hello Telegram!");
+        mock.assert();
+
+        assert!(matches!(
+            TelegramSink::builder().server_url_str("not a url"),
+            Err(Error::ParseUrl(_))
+        ));
+    }
+
+    #[test]
+    fn raw_payload() {
+        let rendered = render_logs(
+            TelegramSink::builder()
+                .bot_token(BOT_TOKEN)
+                .recipient(-1001234567890)
+                .source_tag(SourceTag {
+                    pid: true,
+                    ..Default::default()
+                })
+                .code_block_multiline(true)
+                .raw_payload(true),
+            |logger| info!(logger: logger, "*pre-formatted*\nmessage", kv: { a = 1 }),
+        );
+        assert_eq!(rendered, ["*pre-formatted*\nmessage"]);
+    }
+
+    #[test]
+    fn follow_chat_migration() {
+        fn mock_migrated(server: &mut mockito::Server) -> mockito::Mock {
+            server
+                .mock("POST", SEND_MESSAGE)
+                .match_body(Matcher::PartialJson(
+                    json!({ "chat_id": -1001234567890i64 }),
+                ))
+                .with_status(400)
+                .with_body(
+                    json!({
+                        "ok": false,
+                        "description": "Bad Request: group chat was upgraded to a supergroup chat",
+                        "parameters": { "migrate_to_chat_id": -1009876543210i64 },
+                    })
+                    .to_string(),
+                )
+                .create()
+        }
+
+        let mut server = mockito::Server::new();
+        let sink = builder(&server).build().unwrap();
+        let migrated = mock_migrated(&mut server);
+        let err = sink.requester.send_log(&Message {
+            text: "Hello Telegram!".into(),
+            disable_notification: false,
+            parse_mode: None,
+            thread_id: None,
+            reply_to: None,
+            buttons: None,
+            level: None,
+        });
+        assert!(matches!(
+            err,
+            Err(Error::ChatMigrated {
+                migrate_to_chat_id: -1009876543210
+            })
+        ));
+        migrated.assert();
+        drop(migrated);
+
+        let sink = Arc::new(
+            builder(&server)
+                .follow_chat_migration(true)
+                .recipient_for(LevelFilter::Equal(Level::Error), -1001234567890)
+                .recipient_for(LevelFilter::Equal(Level::Warn), -1005555555555)
+                .build()
+                .unwrap(),
+        );
+        let logger = Logger::builder()
+            .error_handler(|err| panic!("error handler triggered: {err}"))
+            .sink(sink.clone())
+            .build()
+            .unwrap();
+        let migrated = mock_migrated(&mut server);
+        let followed = mock_send(&mut server, json!({ "chat_id": -1009876543210i64 })).expect(3);
+        let other = mock_send(&mut server, json!({ "chat_id": -1005555555555i64 }));
+        info!(logger: logger, "Hello Telegram!");
+        info!(logger: logger, "Hello Telegram!");
+        // The rule sharing the migrated chat is migrated too, without failing first
+        assert_eq!(
+            sink.requester.chat_id_for(Some(Level::Error)),
+            -1009876543210i64
+        );
+        error!(logger: logger, "Hello Telegram!");
+        warn!(logger: logger, "Hello Telegram!");
+        migrated.assert();
+        followed.assert();
+        other.assert();
+    }
+
+    #[test]
+    fn notify_policy() {
+        let mut server = mockito::Server::new();
+        let logger = build_logger(
+            builder(&server)
+                .notify_policy(NotifyPolicy {
+                    min_level_to_send: Level::Warn,
+                    min_level_for_sound: Level::Error,
+                })
+                .build()
+                .unwrap(),
+        );
+
+        let mock = server.mock("POST", SEND_MESSAGE).expect(0).create();
+        info!(logger: logger, "Hello Telegram!");
+        mock.assert();
+        drop(mock);
+
+        let mock = mock_silent(&mut server, true);
+        warn!(logger: logger, "Hello Telegram!");
+        mock.assert();
+
+        let mock = mock_silent(&mut server, false);
+        error!(logger: logger, "Hello Telegram!");
+        mock.assert();
+    }
+
+    #[test]
+    fn user_id_recipient() {
+        let mut server = mockito::Server::new();
+        let user = build_logger(
+            builder(&server)
+                .recipient(Recipient::user_id(123456789).unwrap())
+                .build()
+                .unwrap(),
+        );
+        let channel = build_logger(builder(&server).build().unwrap());
+
+        let mock = mock_send(&mut server, json!({ "chat_id": 123456789 }));
+        info!(logger: user, "Hello Telegram!");
+        mock.assert();
+
+        let mock = mock_send(&mut server, json!({ "chat_id": -1001234567890i64 }));
+        info!(logger: channel, "Hello Telegram!");
+        mock.assert();
+    }
+
+    #[test]
+    fn transport() {
+        #[derive(Clone, Default)]
+        struct Capture(Arc<std::sync::Mutex<Vec<(String, serde_json::Value)>>>);
+
+        impl Transport for Capture {
+            fn call(&self, method: &str, payload: &serde_json::Value) -> Result<serde_json::Value> {
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push((method.to_string(), payload.clone()));
+                Ok(json!({ "ok": true, "result": { "message_thread_id": 114 } }))
+            }
+        }
+
+        let capture = Capture::default();
+        let logger = build_logger(
+            TelegramSink::builder()
+                .error_handler(|err| panic!("error handler triggered: {err}"))
+                .transport(capture.clone())
+                .bot_token(BOT_TOKEN)
+                .recipient(-1001234567890)
+                .create_topic("logs", None)
+                .formatter(PatternFormatter::new(pattern!("{payload}")))
+                .build()
+                .unwrap(),
+        );
+        info!(logger: logger, "Hello Telegram!");
+
+        let calls = capture.0.lock().unwrap();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].0, "createForumTopic");
+        assert_eq!(calls[1].0, "sendMessage");
+        assert_eq!(calls[1].1["text"], "Hello Telegram!");
+        assert_eq!(calls[1].1["message_thread_id"], 114);
+    }
+
+    #[test]
+    fn auto_delete() {
+        let mut server = mockito::Server::new();
+        let logger = build_logger(
+            builder(&server)
+                .auto_delete(Duration::from_millis(100))
+                .forward_on(LevelFilter::All, -1009876543210, 7)
+                .build()
+                .unwrap(),
+        );
+
+        let mock_sent = |server: &mut mockito::Server, method, message_id| {
+            server
+                .mock(
+                    "POST",
+                    format!("/bot1234567890:AbCdEfGhiJkLmNoPq1R2s3T4u5V6w7X8y9z/{method}").as_str(),
+                )
+                .with_body(
+                    json!({
+                        "ok": true,
+                        "result": { "message_id": message_id, "chat": { "id": -1001234567890i64 } },
+                    })
+                    .to_string(),
+                )
+                .create()
+        };
+        let mock_deleted = |server: &mut mockito::Server, message_id| {
+            server
+                .mock(
+                    "POST",
+                    "/bot1234567890:AbCdEfGhiJkLmNoPq1R2s3T4u5V6w7X8y9z/deleteMessage",
+                )
+                .match_body(Matcher::Json(
+                    json!({ "chat_id": -1001234567890i64, "message_id": message_id }),
+                ))
+                .with_body(json!({ "ok": true, "result": true }).to_string())
+                .create()
+        };
+        let sent = mock_sent(&mut server, "sendMessage", 42);
+        let forwarded = mock_sent(&mut server, "forwardMessage", 43);
+        let deleted = [mock_deleted(&mut server, 42), mock_deleted(&mut server, 43)];
+        info!(logger: logger, "Hello Telegram!");
+        sent.assert();
+        forwarded.assert();
+        assert!(deleted.iter().all(|deleted| !deleted.matched()));
+
+        for _ in 0..50 {
+            if deleted.iter().all(mockito::Mock::matched) {
+                break;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+        deleted.iter().for_each(mockito::Mock::assert);
+    }
+
+    #[test]
+    fn thread_id_key() {
+        let mut server = mockito::Server::new();
+        let logger = build_logger(
+            builder(&server)
+                .recipient(
+                    Recipient::builder()
+                        .chat_id(-1001234567890)
+                        .thread_id(1)
+                        .build(),
+                )
+                .thread_id_key("topic")
+                .build()
+                .unwrap(),
+        );
+
+        let mock_thread = |server: &mut mockito::Server, thread_id| {
+            mock_send(server, json!({ "message_thread_id": thread_id }))
+        };
+
+        let mock = mock_thread(&mut server, 114);
+        info!(logger: logger, "Hello Telegram!", kv: { topic = 114 });
+        mock.assert();
+
+        let mock = mock_thread(&mut server, 1);
+        info!(logger: logger, "Hello Telegram!");
+        info!(logger: logger, "Hello Telegram!", kv: { topic = "abc" });
+        mock.expect(2).assert();
+    }
+
+    #[test]
+    fn thread_by_key() {
+        let mut server = mockito::Server::new();
+        let logger = build_logger(builder(&server).thread_by_key("trace_id").build().unwrap());
+        let mut mock = |text: &str, message_id: u64| {
+            server
+                .mock("POST", SEND_MESSAGE)
+                .match_body(Matcher::PartialJson(json!({ "text": text })))
+                .with_body(
+                    json!({ "ok": true, "result": { "message_id": message_id } }).to_string(),
+                )
+                .create()
+        };
+
+        let first = mock("payment failed", 42);
+        let other = mock("login failed", 43);
+        let untraced = mock("disk full", 44);
+        info!(logger: logger, "payment failed", kv: { trace_id = "a" });
+        info!(logger: logger, "login failed", kv: { trace_id = "b" });
+        info!(logger: logger, "disk full");
+        for mock in [first, other, untraced] {
+            mock.assert();
+        }
+
+        let replies = [
+            ("refund failed", "a", 42),
+            ("retry failed", "b", 43),
+            ("refund failed again", "a", 42),
+        ]
+        .map(|(text, trace_id, message_id)| {
+            let mock = server
+                .mock("POST", SEND_MESSAGE)
+                .match_body(Matcher::PartialJson(json!({
+                    "text": text,
+                    "reply_parameters": { "message_id": message_id },
+                })))
+                .with_body(json!({ "ok": true, "result": { "message_id": 100 } }).to_string())
+                .create();
+            info!(logger: logger, "{text}", kv: { trace_id = trace_id });
+            mock
+        });
+        for mock in replies {
+            mock.assert();
+        }
+    }
+
+    #[test]
+    fn http2_prior_knowledge() {
+        use std::{io::Read, net::TcpListener};
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let server_url = format!("http://{}", listener.local_addr().unwrap());
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut preface = [0; 24];
+            stream.read_exact(&mut preface).unwrap();
+            preface
+        });
+
+        let sink = TelegramSink::builder()
+            .server_url_str(&server_url)
+            .unwrap()
+            .bot_token(BOT_TOKEN)
+            .recipient(-1001234567890)
+            .http2_prior_knowledge(true)
+            .build()
+            .unwrap();
+        let logger = Logger::builder()
+            .sink(Arc::new(sink))
+            .error_handler(|_| {})
+            .build()
+            .unwrap();
+        // The server never responds, it only checks the connection preface
+        let sender = thread::spawn(move || info!(logger: logger, "Hello Telegram!"));
+
+        assert_eq!(&server.join().unwrap(), b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n");
+        sender.join().unwrap();
+    }
+
+    #[test]
+    fn builder_with_source() {
+        let rendered = render_logs(
+            TelegramSink::builder_with_source(false)
+                .bot_token(BOT_TOKEN)
+                .recipient(-1001234567890),
+            |logger| info!(logger: logger, "Hello Telegram!"),
+        );
+        assert_eq!(rendered, ["#log #info Hello Telegram!"]);
+    }
+
+    #[test]
+    fn on_blocked() {
+        static ERRORS: AtomicU64 = AtomicU64::new(0);
+
+        let mut server = mockito::Server::new();
+        let _blocked = server
+            .mock("POST", SEND_MESSAGE)
+            .with_status(403)
+            .with_body(
+                json!({
+                    "ok": false,
+                    "error_code": 403,
+                    "description": "Forbidden: bot was blocked by the user",
+                })
+                .to_string(),
+            )
+            .create();
+
+        for (policy, sends, errors) in [
+            (BlockedPolicy::Error, 3, 3),
+            (BlockedPolicy::Ignore, 3, 0),
+            (BlockedPolicy::Disable, 1, 1),
+        ] {
+            ERRORS.store(0, Ordering::Relaxed);
+            let sink = Arc::new(
+                builder(&server)
+                    .recipient(Recipient::user_id(123456789).unwrap())
+                    .on_blocked(policy)
+                    .build()
+                    .unwrap(),
+            );
+            let logger = Logger::builder()
+                .sink(sink.clone())
+                .error_handler(|err: spdlog::Error| {
+                    assert!(err.to_string().contains("bot was blocked"));
+                    ERRORS.fetch_add(1, Ordering::Relaxed);
+                })
+                .build()
+                .unwrap();
+            for _ in 0..3 {
+                info!(logger: logger, "Hello Telegram!");
+            }
+            let stats = sink.stats();
+            assert_eq!(stats.failures(), sends, "{policy:?}");
+            assert_eq!(ERRORS.load(Ordering::Relaxed), errors, "{policy:?}");
+        }
+    }
+
+    #[test]
+    fn category_icons() {
+        let rendered = render_logs(
+            render_builder().category_icons(
+                "category",
+                HashMap::from([
+                    ("deploy".into(), "🚀".into()),
+                    ("security".into(), "🛡️".into()),
+                ]),
+            ),
+            |logger| {
+                info!(logger: logger, "Deployed", kv: { category = "deploy" });
+                warn!(logger: logger, "Login failed", kv: { category = "security" });
+                info!(logger: logger, "Hello Telegram!", kv: { category = "other" });
+                info!(logger: logger, "Hello Telegram!");
+            },
+        );
+        assert_eq!(
+            rendered,
+            [
+                "🚀 Deployed",
+                "🛡️ Login failed",
+                "Hello Telegram!",
+                "Hello Telegram!"
+            ]
+        );
+    }
+
+    #[test]
+    fn render() {
+        let server = mockito::Server::new();
+        let output = Output::default();
+        let sink = Arc::new(
+            builder(&server)
+                .level_filter(LevelFilter::MoreSevereEqual(Level::Info))
+                .source_tag(SourceTag {
+                    custom: Some("region=eu".into()),
+                    ..Default::default()
+                })
+                .code_block_multiline(true)
+                .dry_run_writer(output.clone())
+                .build()
+                .unwrap(),
+        );
+        // Captures the records being sent
+        let records = Arc::new(std::sync::Mutex::new(vec![]));
+        sink.set_silence_fn(Arc::new({
+            let records = records.clone();
+            move |record| {
+                records.lock().unwrap().push(record.to_owned());
+                false
+            }
+        }));
+        let logger = Logger::builder()
+            .sink(sink.clone())
+            .level_filter(LevelFilter::All)
+            .build()
+            .unwrap();
 
-impl TelegramSinkBuilder<String, Recipient> {
-    /// Builds a `TelegramSink`.
-    pub fn build(self) -> Result<TelegramSink> {
-        Ok(TelegramSink {
-            prop: self.prop,
-            silence: Atomic::new(self.silence),
-            requester: Requester::new(
-                self.server_url
-                    .map_or_else(|| Url::parse("https://api.telegram.org"), Ok)
-                    .map_err(Error::ParseUrl)?,
-                &self.bot_token,
-                self.recipient,
-            )?,
-        })
+        info!(logger: logger, "Hello\nTelegram!");
+        let records = records.lock().unwrap();
+        let output = output.contents();
+        let (_, payload) = output.trim_end().split_once(" sendMessage ").unwrap();
+        let payload = serde_json::from_str::<serde_json::Value>(payload).unwrap();
+
+        let rendered = sink.render(&records[0].as_ref()).unwrap().unwrap();
+        assert_eq!(rendered, "<pre>Hello\nTelegram!</pre>\n[region=eu]");
+        assert_eq!(payload["text"], rendered);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use std::sync::Arc;
+    #[test]
+    fn allow_paid_broadcast() {
+        let mut server = mockito::Server::new();
+        let logger = build_logger(builder(&server).allow_paid_broadcast(true).build().unwrap());
 
-    use mockito::Matcher;
-    use serde_json::json;
+        let mock = mock_send(&mut server, json!({ "allow_paid_broadcast": true }));
+        info!(logger: logger, "Hello Telegram!");
+        mock.assert();
+    }
 
-    use super::*;
+    #[test]
+    fn reply_to_if() {
+        let server = mockito::Server::new();
+        let output = Output::default();
+        let logger = build_logger(
+            builder(&server)
+                .reply_to_if(LevelFilter::MoreSevereEqual(Level::Error), 514)
+                .dry_run_writer(output.clone())
+                .build()
+                .unwrap(),
+        );
+        error!(logger: logger, "disk full");
+        info!(logger: logger, "disk ok");
+
+        let payloads = output
+            .contents()
+            .lines()
+            .map(|line| {
+                let payload = line
+                    .strip_prefix("[spdlog-telegram dry-run] sendMessage ")
+                    .unwrap();
+                json::from_str::<json::Value>(payload).unwrap()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(payloads.len(), 2);
+        assert_eq!(payloads[0]["text"], "disk full");
+        assert_eq!(
+            payloads[0]["reply_parameters"],
+            json!({ "message_id": 514 })
+        );
+        assert_eq!(payloads[1]["text"], "disk ok");
+        assert!(payloads[1].get("reply_parameters").is_none());
+    }
 
     #[test]
-    fn request() {
+    fn endpoint_redacted() {
+        let server = mockito::Server::new();
+        let sink = builder(&server).build().unwrap();
+        let endpoint = sink.endpoint_redacted().unwrap();
+        assert_eq!(endpoint, format!("{}/bot<token>/sendMessage", server.url()));
+        assert!(!endpoint.contains(BOT_TOKEN));
+
+        struct Null;
+
+        impl Transport for Null {
+            fn call(&self, _: &str, _: &serde_json::Value) -> Result<serde_json::Value> {
+                Ok(json!({ "ok": true, "result": {} }))
+            }
+        }
+
+        let sink = builder(&server).transport(Null).build().unwrap();
+        assert_eq!(sink.endpoint_redacted(), None);
+    }
+
+    #[test]
+    fn build_with_report() {
         let mut server = mockito::Server::new();
+        let (sink, report) = builder(&server)
+            .parse_mode(ParseMode::Html)
+            .http2_prior_knowledge(true)
+            .build_with_report()
+            .unwrap();
+        assert!(!report.token_validated());
+        assert_eq!(sink.token_validated(), None);
+        assert_eq!(
+            report.endpoint_redacted(),
+            Some(format!("{}/bot<token>/sendMessage", server.url()).as_str())
+        );
+        assert_eq!(report.default_pattern(), None);
+        assert!(report.link_preview());
+        assert!(report.http2_prior_knowledge());
+        assert!(!report.dry_run());
 
-        let error_handler = |err| panic!("error handler triggered: {err}");
-        let sink = Arc::new(
-            TelegramSink::builder()
-                .error_handler(error_handler)
-                .server_url(Url::parse(&server.url()).unwrap())
-                .bot_token("1234567890:AbCdEfGhiJkLmNoPq1R2s3T4u5V6w7X8y9z")
+        let (_, report) = TelegramSink::builder_with_source(false)
+            .bot_token(BOT_TOKEN)
+            .recipient(-1001234567890)
+            .dry_run_writer(io::sink())
+            .build_with_report()
+            .unwrap();
+        assert_eq!(
+            report.default_pattern(),
+            Some("#log #{level} {payload} {kv}")
+        );
+        assert!(!report.link_preview());
+        assert!(!report.http2_prior_knowledge());
+        assert!(report.dry_run());
+
+        server
+            .mock("POST", Matcher::Regex("/getChat$".into()))
+            .with_body(
+                json!({ "ok": true, "result": { "linked_chat_id": -1009876543210_i64 } })
+                    .to_string(),
+            )
+            .create();
+        let (sink, report) = builder(&server)
+            .recipient(Recipient::linked_discussion(-1001234567890))
+            .build_with_report()
+            .unwrap();
+        assert!(report.token_validated());
+        assert_eq!(sink.token_validated(), Some(true));
+    }
+
+    #[test]
+    fn default_pattern() {
+        let server = mockito::Server::new();
+        let sink = builder(&server).build().unwrap();
+        assert_eq!(sink.default_pattern(), None);
+
+        for source in [true, false] {
+            let sink = TelegramSink::builder_with_source(source)
+                .bot_token(BOT_TOKEN)
+                .recipient(-1001234567890)
+                .build()
+                .unwrap();
+            assert_eq!(
+                sink.default_pattern(),
+                Some(TelegramSink::default_pattern_for(source).as_str())
+            );
+        }
+
+        let sink = TelegramSink::builder()
+            .bot_token(BOT_TOKEN)
+            .recipient(-1001234567890)
+            .include_logger_name(true)
+            .build()
+            .unwrap();
+        assert!(
+            sink.default_pattern()
+                .unwrap()
+                .starts_with("[{logger}] #log #{level} {payload} {kv}")
+        );
+    }
+
+    #[test]
+    fn source_as_reply() {
+        let mut server = mockito::Server::new();
+        let sink = TelegramSink::builder_with_source(true)
+            .error_handler(|err| panic!("error handler triggered: {err}"))
+            .server_url(Url::parse(&server.url()).unwrap())
+            .bot_token(BOT_TOKEN)
+            .recipient(-1001234567890)
+            .source_as_reply(true)
+            .build()
+            .unwrap();
+        assert_eq!(sink.default_pattern(), Some("#log #{level} {payload} {kv}"));
+        let logger = build_logger(sink);
+
+        let mock = server
+            .mock("POST", SEND_MESSAGE)
+            .match_body(Matcher::PartialJson(
+                json!({ "text": "#log #info Hello Telegram!" }),
+            ))
+            .with_body(json!({ "ok": true, "result": { "message_id": 42 } }).to_string())
+            .create();
+        // Records have no source location without crate feature `source-location`
+        // of spdlog-rs, then only the main message is sent.
+        let reply = server
+            .mock("POST", SEND_MESSAGE)
+            .match_body(Matcher::AllOf(vec![
+                Matcher::PartialJson(json!({
+                    "disable_notification": true,
+                    "reply_parameters": { "message_id": 42 },
+                })),
+                Matcher::Regex(r#""text":"@src/lib.rs:\d+""#.into()),
+            ]))
+            .with_body(json!({ "ok": true, "result": { "message_id": 43 } }).to_string())
+            .expect(usize::from(spdlog::source_location_current!().is_some()))
+            .create();
+        info!(logger: logger, "Hello Telegram!");
+        mock.assert();
+        reply.assert();
+    }
+
+    #[test]
+    fn inline_buttons() {
+        let mut server = mockito::Server::new();
+        let logger = build_logger(
+            builder(&server)
+                .inline_buttons(|record| {
+                    LevelFilter::MoreSevereEqual(Level::Error)
+                        .test(record.level())
+                        .then(|| {
+                            vec![
+                                vec![Button::url("Dashboard", "https://example.com/d")],
+                                vec![
+                                    Button::callback("Ack", "ack:1"),
+                                    Button::callback("Mute", "mute:1"),
+                                ],
+                            ]
+                        })
+                })
+                .build()
+                .unwrap(),
+        );
+
+        let mock = mock_send(
+            &mut server,
+            json!({
+                "text": "disk full",
+                "reply_markup": {
+                    "inline_keyboard": [
+                        [{ "text": "Dashboard", "url": "https://example.com/d" }],
+                        [
+                            { "text": "Ack", "callback_data": "ack:1" },
+                            { "text": "Mute", "callback_data": "mute:1" },
+                        ],
+                    ],
+                },
+            }),
+        );
+        error!(logger: logger, "disk full");
+        mock.assert();
+
+        let mock = server
+            .mock("POST", SEND_MESSAGE)
+            .match_body(Matcher::Json(json!({
+                "chat_id": -1001234567890i64,
+                "message_thread_id": null,
+                "text": "disk ok",
+                "link_preview_options": { "is_disabled": true },
+                "disable_notification": false,
+            })))
+            .with_body(json!({ "ok": true, "result": {} }).to_string())
+            .create();
+        info!(logger: logger, "disk ok");
+        mock.assert();
+    }
+
+    #[test]
+    fn parse_mode_key() {
+        let server = mockito::Server::new();
+        let output = Output::default();
+        let logger = build_logger(
+            builder(&server)
+                .parse_mode(ParseMode::Html)
+                .parse_mode_key("parse_mode")
+                .dry_run_writer(output.clone())
+                .build()
+                .unwrap(),
+        );
+        info!(logger: logger, "<b>default</b>");
+        info!(logger: logger, "*markdown*", kv: { parse_mode = "MarkdownV2" });
+        info!(logger: logger, "a < b", kv: { parse_mode = "none" });
+        info!(logger: logger, "<i>unknown</i>", kv: { parse_mode = "Markdown" });
+
+        let parse_modes = output
+            .contents()
+            .lines()
+            .map(|line| {
+                let payload = line
+                    .strip_prefix("[spdlog-telegram dry-run] sendMessage ")
+                    .unwrap();
+                json::from_str::<json::Value>(payload).unwrap()["parse_mode"].clone()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            parse_modes,
+            [
+                json!("HTML"),
+                json!("MarkdownV2"),
+                json::Value::Null,
+                json!("HTML")
+            ]
+        );
+    }
+
+    #[test]
+    fn allow_sending_without_reply() {
+        let server = mockito::Server::new();
+        let output = Output::default();
+        let logger = build_logger(
+            builder(&server)
                 .recipient(
                     Recipient::builder()
                         .chat_id(-1001234567890)
-                        .thread_id(114)
-                        .reply_to(514)
+                        .reply_to_with_quote(1, Quote::new("web-3"))
+                        .build(),
+                )
+                .reply_to_if(LevelFilter::MoreSevereEqual(Level::Error), 514)
+                .allow_sending_without_reply(true)
+                .dry_run_writer(output.clone())
+                .build()
+                .unwrap(),
+        );
+        error!(logger: logger, "disk full");
+        info!(logger: logger, "disk ok");
+
+        let replies = output
+            .contents()
+            .lines()
+            .map(|line| {
+                let payload = line
+                    .strip_prefix("[spdlog-telegram dry-run] sendMessage ")
+                    .unwrap();
+                json::from_str::<json::Value>(payload).unwrap()["reply_parameters"].clone()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            replies,
+            [
+                json!({ "message_id": 514, "allow_sending_without_reply": true }),
+                json!({
+                    "message_id": 1,
+                    "chat_id": null,
+                    "quote": "web-3",
+                    "allow_sending_without_reply": true,
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn reply_across_threads() {
+        let server = mockito::Server::new();
+        let output = Output::default();
+        let payload = |recipient: Recipient| {
+            let logger = build_logger(
+                builder(&server)
+                    .recipient(recipient)
+                    .dry_run_writer(output.clone())
+                    .build()
+                    .unwrap(),
+            );
+            info!(logger: logger, "Hello Telegram!");
+            let line = output.contents().lines().last().unwrap().to_string();
+            json::from_str::<json::Value>(
+                line.strip_prefix("[spdlog-telegram dry-run] sendMessage ")
+                    .unwrap(),
+            )
+            .unwrap()
+        };
+
+        // Logs to topic 114, replies to message 1 in the General topic
+        let sent = payload(
+            Recipient::builder()
+                .chat_id(-1001234567890)
+                .thread_id(114)
+                .reply_to(1)
+                .build(),
+        );
+        assert_eq!(sent["message_thread_id"], 114);
+        assert_eq!(sent["reply_parameters"]["message_id"], 1);
+
+        let sent = payload(
+            Recipient::builder()
+                .chat_id(-1001234567890)
+                .thread_id(114)
+                .reply_to_link("https://t.me/c/1234567890/1")
+                .unwrap()
+                .build(),
+        );
+        assert_eq!(sent["message_thread_id"], 114);
+        assert_eq!(
+            sent["reply_parameters"],
+            json!({ "message_id": 1, "chat_id": -1001234567890i64 })
+        );
+    }
+
+    #[test]
+    fn payload_template() {
+        let mut server = mockito::Server::new();
+        let logger = build_logger(
+            builder(&server)
+                .payload_template(Map::from_iter([
+                    ("protect_content".into(), json!(true)),
+                    ("text".into(), json!("overridden")),
+                    ("chat_id".into(), json!(42)),
+                ]))
+                .build()
+                .unwrap(),
+        );
+
+        let mock = mock_send(
+            &mut server,
+            json!({
+                "protect_content": true,
+                "text": "Hello Telegram!",
+                "chat_id": -1001234567890i64,
+                "disable_notification": false,
+            }),
+        );
+        info!(logger: logger, "Hello Telegram!");
+        mock.assert();
+    }
+
+    #[test]
+    fn reply_to_with_quote() {
+        let mut server = mockito::Server::new();
+        let logger = build_logger(
+            builder(&server)
+                .recipient(
+                    Recipient::builder()
+                        .chat_id(-1001234567890)
+                        .reply_to_with_quote(514, Quote::new("web-3").position(28))
                         .build(),
                 )
-                .silence(LevelFilter::MoreVerboseEqual(Level::Info))
                 .build()
                 .unwrap(),
         );
+
+        let mock = mock_send(
+            &mut server,
+            json!({
+                "reply_parameters": {
+                    "message_id": 514,
+                    "quote": "web-3",
+                    "quote_position": 28,
+                }
+            }),
+        );
+        info!(logger: logger, "Hello Telegram!");
+        mock.assert();
+    }
+
+    #[test]
+    fn silence_round_trip() {
+        let server = mockito::Server::new();
+        let sink = builder(&server).build().unwrap();
+        for level_filter in [
+            LevelFilter::Off,
+            LevelFilter::All,
+            LevelFilter::Equal(Level::Critical),
+            LevelFilter::NotEqual(Level::Error),
+            LevelFilter::MoreSevere(Level::Warn),
+            LevelFilter::MoreSevereEqual(Level::Info),
+            LevelFilter::MoreVerbose(Level::Debug),
+            LevelFilter::MoreVerboseEqual(Level::Trace),
+        ] {
+            sink.set_silence(level_filter);
+            assert_eq!(sink.silence(), level_filter);
+        }
+    }
+
+    #[test]
+    fn set_level_filter() {
+        let mut server = mockito::Server::new();
+        let sink = Arc::new(builder(&server).build().unwrap());
         let logger = Logger::builder()
-            .error_handler(error_handler)
+            .error_handler(|err| panic!("error handler triggered: {err}"))
             .sink(sink.clone())
             .build()
             .unwrap();
+        assert_eq!(sink.level_filter(), LevelFilter::All);
 
-        let mut mocker = |level| {
+        let mock = mock_text(&mut server, "warn").expect(1);
+        sink.set_level_filter(LevelFilter::MoreSevereEqual(Level::Error));
+        assert_eq!(
+            sink.level_filter(),
+            LevelFilter::MoreSevereEqual(Level::Error)
+        );
+        warn!(logger: logger, "warn");
+        sink.set_level_filter(LevelFilter::MoreSevereEqual(Level::Warn));
+        warn!(logger: logger, "warn");
+        mock.assert();
+    }
+
+    #[test]
+    fn trim_output() {
+        for (trim, code_block, payload, expected) in [
+            (true, false, "Hello Telegram!", "Hello Telegram!"),
+            (false, false, "Hello Telegram!", "\nHello Telegram! \n"),
+            (
+                true,
+                true,
+                "  at main\n  at foo",
+                "<pre>  at main\n  at foo</pre>",
+            ),
+        ] {
+            let rendered = render_logs(
+                render_builder()
+                    .formatter(PatternFormatter::new(pattern!("\n{payload} \n")))
+                    .trim_output(trim)
+                    .code_block_multiline(code_block),
+                |logger| info!(logger: logger, "{payload}"),
+            );
+            assert_eq!(rendered, [expected]);
+        }
+    }
+
+    #[test]
+    fn link_preview() {
+        let mut server = mockito::Server::new();
+        for (builder, disabled) in [
+            (builder(&server), true),
+            (builder(&server).parse_mode(ParseMode::Html), false),
+            (
+                builder(&server)
+                    .parse_mode(ParseMode::Html)
+                    .link_preview(false),
+                true,
+            ),
+            (builder(&server).link_preview(true), false),
+        ] {
+            let logger = build_logger(builder.build().unwrap());
+            let mock = mock_send(
+                &mut server,
+                json!({
+                    "link_preview_options": { "is_disabled": disabled }
+                }),
+            );
+            info!(logger: logger, "Hello Telegram!");
+            mock.assert();
+        }
+    }
+
+    #[test]
+    fn legacy_link_preview() {
+        let server = mockito::Server::new();
+        for (builder, disabled) in [
+            (builder(&server), true),
+            (builder(&server).link_preview(true), false),
+        ] {
+            let output = Output::default();
+            let logger = build_logger(
+                builder
+                    .legacy_link_preview(true)
+                    .dry_run_writer(output.clone())
+                    .build()
+                    .unwrap(),
+            );
+            info!(logger: logger, "Hello Telegram!");
+
+            let line = output.contents();
+            let sent = json::from_str::<json::Value>(
+                line.trim_end()
+                    .strip_prefix("[spdlog-telegram dry-run] sendMessage ")
+                    .unwrap(),
+            )
+            .unwrap();
+            assert_eq!(sent["disable_web_page_preview"], disabled);
+            assert!(sent.get("link_preview_options").is_none());
+        }
+    }
+
+    #[test]
+    fn build_and_test() {
+        let mut server = mockito::Server::new();
+        let mock = mock_send(
+            &mut server,
+            json!({
+                "text": "✅ spdlog-telegram connected",
+                "disable_notification": true,
+            }),
+        );
+        let sink = builder(&server).build_and_test().unwrap();
+        assert_eq!(sink.token_validated(), Some(true));
+        mock.assert();
+        drop(mock);
+
+        let sink = builder(&server)
+            .dry_run_writer(io::sink())
+            .build_and_test()
+            .unwrap();
+        assert_eq!(sink.token_validated(), Some(false));
+
+        server
+            .mock("POST", SEND_MESSAGE)
+            .with_status(401)
+            .with_body(json!({ "ok": false, "description": "Unauthorized" }).to_string())
+            .create();
+        assert!(matches!(
+            builder(&server).build_and_test(),
+            Err(Error::TelegramApi {
+                description: Some(description),
+                parameters: None,
+            }) if description == "Unauthorized"
+        ));
+    }
+
+    #[test]
+    fn prewarm() {
+        let mut server = mockito::Server::new();
+        let sink = builder(&server).build().unwrap();
+        let mock = server
+            .mock(
+                "POST",
+                "/bot1234567890:AbCdEfGhiJkLmNoPq1R2s3T4u5V6w7X8y9z/getMe",
+            )
+            .with_body(json!({ "ok": true, "result": { "is_bot": true } }).to_string())
+            .expect(2)
+            .create();
+        sink.prewarm().unwrap();
+        sink.prewarm().unwrap();
+        mock.assert();
+    }
+
+    #[test]
+    fn telegram_api_parameters() {
+        let mut server = mockito::Server::new();
+        server
+            .mock("POST", SEND_MESSAGE)
+            .with_status(429)
+            .with_body(
+                json!({
+                    "ok": false,
+                    "error_code": 429,
+                    "description": "Too Many Requests: retry after 5",
+                    "parameters": { "retry_after": 5, "flood_scope": "chat" },
+                })
+                .to_string(),
+            )
+            .create();
+        match builder(&server).build_and_test() {
+            Err(Error::TelegramApi {
+                description,
+                parameters,
+            }) => {
+                assert_eq!(
+                    description.as_deref(),
+                    Some("Too Many Requests: retry after 5")
+                );
+                assert_eq!(
+                    parameters,
+                    Some(json!({ "retry_after": 5, "flood_scope": "chat" }))
+                );
+            }
+            res => panic!("unexpected result: {:?}", res.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn max_request_bytes() {
+        let server = mockito::Server::new();
+        let output = Output::default();
+        builder(&server)
+            .dry_run_writer(output.clone())
+            .build_and_test()
+            .unwrap();
+        let size = output
+            .contents()
+            .trim_end()
+            .strip_prefix("[spdlog-telegram dry-run] sendMessage ")
+            .unwrap()
+            .len();
+
+        let build = |max_bytes| {
+            builder(&server)
+                .max_request_bytes(max_bytes)
+                .dry_run_writer(io::sink())
+                .build_and_test()
+                .map(|_| ())
+        };
+        build(size).unwrap();
+        match build(size - 1) {
+            Err(Error::RequestTooLarge { size: s, limit }) => {
+                assert_eq!((s, limit), (size, size - 1));
+            }
+            res => panic!("unexpected result: {res:?}"),
+        }
+    }
+
+    #[test]
+    fn response_fields() {
+        let test = |status, body: json::Value| {
+            let mut server = mockito::Server::new();
             server
-                .mock(
-                    "POST",
-                    "/bot1234567890:AbCdEfGhiJkLmNoPq1R2s3T4u5V6w7X8y9z/sendMessage",
-                )
-                .match_header("content-type", "application/json")
-                .match_body(Matcher::PartialJson(json!({
-                    "chat_id": -1001234567890_i64,
-                    "disable_notification": sink.silence().test(level),
-                    "link_preview_options": {
-                        "is_disabled": true
-                    },
-                    "message_thread_id": 114,
-                    "text": format!("#log #{} Hello Telegram! k=v", level.as_str()),
-                    "reply_parameters": {
-                        "message_id": 514,
-                    }
-                })))
-                .with_header("content-type", "application/json")
-                .with_body(json!({ "ok": true, "result": { /* omitted */ }}).to_string())
-                .create()
+                .mock("POST", SEND_MESSAGE)
+                .with_status(status)
+                .with_body(body.to_string())
+                .create();
+            builder(&server)
+                .ok_field("success")
+                .description_field("message")
+                .build_and_test()
+                .map(|_| ())
         };
 
-        let mock = mocker(Level::Info);
-        info!(logger: logger, "Hello Telegram!", kv: { k = "v" });
+        test(200, json!({ "success": true, "result": {} })).unwrap();
+        match test(400, json!({ "success": false, "message": "Bad Request" })) {
+            Err(Error::TelegramApi { description, .. }) => {
+                assert_eq!(description.as_deref(), Some("Bad Request"));
+            }
+            res => panic!("unexpected result: {res:?}"),
+        }
+        // The default field names are no longer recognized.
+        assert!(matches!(
+            test(200, json!({ "ok": true, "result": {} })),
+            Err(Error::UnexpectedResponse { .. })
+        ));
+    }
+
+    #[test]
+    fn environment_badge() {
+        let rendered = render_logs(
+            render_builder()
+                .environment_badge("PROD")
+                .parse_mode(ParseMode::MarkdownV2)
+                .category_icons("category", HashMap::from([("deploy".into(), "🚀".into())])),
+            |logger| info!(logger: logger, "Deployed", kv: { category = "deploy" }),
+        );
+        assert_eq!(rendered, ["\\[PROD\\]\n🚀 Deployed"]);
+    }
+
+    #[test]
+    fn before_send() {
+        let mut server = mockito::Server::new();
+        let sink = builder(&server)
+            .before_send(|payload| {
+                payload["protect_content"] = true.into();
+                payload["text"] != "drop me"
+            })
+            .build()
+            .unwrap();
+        let sink = Arc::new(sink);
+        let logger = Logger::builder()
+            .error_handler(|err| panic!("error handler triggered: {err}"))
+            .sink(sink.clone())
+            .build()
+            .unwrap();
+
+        let mock = mock_send(
+            &mut server,
+            json!({ "text": "hello", "protect_content": true }),
+        );
+        info!(logger: logger, "hello");
+        info!(logger: logger, "drop me");
         mock.assert();
 
-        let mock = mocker(Level::Error);
-        error!(logger: logger, "Hello Telegram!", kv: { k = "v" });
+        let stats = sink.stats();
+        assert_eq!(stats.total_delivered(), 1);
+        assert_eq!(stats.failures(), 0);
+    }
+
+    #[test]
+    fn on_latency() {
+        let mut server = mockito::Server::new();
+        let latencies = Arc::new(std::sync::Mutex::new(vec![]));
+        let logger = build_logger({
+            let latencies = latencies.clone();
+            builder(&server)
+                .recipient_for(LevelFilter::MoreSevereEqual(Level::Error), "@alerts")
+                .on_latency(move |latency, chat_id| {
+                    latencies.lock().unwrap().push((latency, chat_id.clone()));
+                })
+                .build()
+                .unwrap()
+        });
+
+        let mock = server
+            .mock("POST", SEND_MESSAGE)
+            .with_body_from_request(|_| {
+                thread::sleep(Duration::from_millis(50));
+                json!({ "ok": true, "result": {} }).to_string().into()
+            })
+            .expect(2)
+            .create();
+        info!(logger: logger, "hello");
+        error!(logger: logger, "oops");
         mock.assert();
+
+        let latencies = latencies.lock().unwrap();
+        assert_eq!(
+            latencies
+                .iter()
+                .map(|(_, chat_id)| chat_id.clone())
+                .collect::<Vec<_>>(),
+            [json!(-1001234567890_i64), json!("@alerts")]
+        );
+        assert!(
+            latencies
+                .iter()
+                .all(|(latency, _)| *latency >= Duration::from_millis(50))
+        );
+    }
+
+    #[test]
+    fn reply_parameters() {
+        let reply_parameters = |recipient: Recipient| {
+            let server = mockito::Server::new();
+            let output = Output::default();
+            let logger = build_logger(
+                builder(&server)
+                    .recipient(recipient)
+                    .allow_sending_without_reply(true)
+                    .dry_run_writer(output.clone())
+                    .build()
+                    .unwrap(),
+            );
+            info!(logger: logger, "hello");
+            let contents = output.contents();
+            let payload = contents
+                .strip_prefix("[spdlog-telegram dry-run] sendMessage ")
+                .unwrap();
+            json::from_str::<json::Value>(payload).unwrap()["reply_parameters"].clone()
+        };
+        let recipient = || Recipient::builder().chat_id(-1001234567890);
+
+        assert_eq!(
+            reply_parameters(recipient().reply_to(514).build()),
+            json!({ "message_id": 514, "chat_id": null, "allow_sending_without_reply": true })
+        );
+        assert_eq!(
+            reply_parameters(
+                recipient()
+                    .reply_to_with_quote(514, Quote::new("web-3").position(13))
+                    .build()
+            ),
+            json!({
+                "message_id": 514,
+                "chat_id": null,
+                "quote": "web-3",
+                "quote_position": 13,
+                "allow_sending_without_reply": true,
+            })
+        );
+        assert_eq!(
+            reply_parameters(
+                recipient()
+                    .reply_to_link("https://t.me/c/1234567890/456")
+                    .unwrap()
+                    .build()
+            ),
+            json!({
+                "message_id": 456,
+                "chat_id": -1001234567890_i64,
+                "allow_sending_without_reply": true,
+            })
+        );
+        assert_eq!(
+            reply_parameters(
+                recipient()
+                    .reply_to_link("https://t.me/my_status/456")
+                    .unwrap()
+                    .build()
+            ),
+            json!({
+                "message_id": 456,
+                "chat_id": "@my_status",
+                "allow_sending_without_reply": true,
+            })
+        );
+        // A later reply replaces the earlier one.
+        assert_eq!(
+            reply_parameters(recipient().reply_to(1).reply_to(514).build()),
+            json!({ "message_id": 514, "chat_id": null, "allow_sending_without_reply": true })
+        );
     }
 }
@@ -0,0 +1,388 @@
+use spdlog::{
+    Record, StringBuf,
+    formatter::{Formatter, FormatterContext},
+};
+
+use crate::text;
+
+/// Represents the parse mode of message texts.
+///
+/// See [Telegram Bot API: Formatting options][formatting].
+///
+/// [formatting]: https://core.telegram.org/bots/api#formatting-options
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseMode {
+    /// HTML style.
+    Html,
+    /// MarkdownV2 style.
+    MarkdownV2,
+}
+
+impl ParseMode {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            Self::Html => "HTML",
+            Self::MarkdownV2 => "MarkdownV2",
+        }
+    }
+}
+
+// Escapes plain text for the given parse mode, so that it's rendered literally.
+pub(crate) fn escape(text: &str, parse_mode: Option<ParseMode>) -> String {
+    match parse_mode {
+        None => text.to_string(),
+        Some(ParseMode::Html) => escape_html(text),
+        Some(ParseMode::MarkdownV2) => escape_with(text, |ch| {
+            matches!(
+                ch,
+                '_' | '*'
+                    | '['
+                    | ']'
+                    | '('
+                    | ')'
+                    | '~'
+                    | '`'
+                    | '>'
+                    | '#'
+                    | '+'
+                    | '-'
+                    | '='
+                    | '|'
+                    | '{'
+                    | '}'
+                    | '.'
+                    | '!'
+                    | '\\'
+            )
+        }),
+    }
+}
+
+/// Escapes text for [`ParseMode::Html`], so that it's rendered literally.
+///
+/// Only `<`, `>` and `&` are escaped, as required by Telegram. Unescaped, they
+/// make Telegram reject the message or misrender it, e.g. for a payload
+/// `"a < b"`.
+///
+/// ## Examples
+///
+/// ```
+/// assert_eq!(
+///     spdlog_telegram::escape_html("<b>Tom & Jerry</b>"),
+///     "&lt;b&gt;Tom &amp; Jerry&lt;/b&gt;"
+/// );
+/// ```
+#[must_use]
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '&' => escaped.push_str("&amp;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+/// A formatter wrapper escaping the output of the inner formatter for
+/// [`ParseMode::Html`].
+///
+/// Use it with a custom formatter if the [parse mode] is HTML, so that logs
+/// containing `<`, `>` or `&` are sent literally. As the whole output is
+/// escaped, tags written by the inner formatter are escaped too, don't wrap it
+/// if it writes intentional markup. See [`escape_html`].
+///
+/// ## Examples
+///
+/// ```
+/// use spdlog::formatter::{PatternFormatter, pattern};
+/// use spdlog_telegram::{HtmlEscaped, ParseMode, TelegramSink};
+///
+/// TelegramSink::builder()
+///     .parse_mode(ParseMode::Html)
+///     .formatter(HtmlEscaped::new(PatternFormatter::new(pattern!(
+///         "{payload}"
+///     ))));
+/// ```
+///
+/// [parse mode]: crate::TelegramSinkBuilder::parse_mode
+#[derive(Clone)]
+pub struct HtmlEscaped<F>(F);
+
+impl<F> HtmlEscaped<F>
+where
+    F: Formatter + Clone,
+{
+    /// Wraps the formatter.
+    #[must_use]
+    pub fn new(formatter: F) -> Self {
+        Self(formatter)
+    }
+}
+
+impl<F> Formatter for HtmlEscaped<F>
+where
+    F: Formatter + Clone,
+{
+    fn format(
+        &self,
+        record: &Record,
+        dest: &mut StringBuf,
+        ctx: &mut FormatterContext,
+    ) -> spdlog::Result<()> {
+        let mut unescaped = StringBuf::new();
+        self.0.format(record, &mut unescaped, ctx)?;
+        dest.push_str(&escape_html(&unescaped));
+        Ok(())
+    }
+}
+
+// Truncates the text like `text::truncate_utf16`, without splitting an HTML
+// entity or a MarkdownV2 escape of the parse mode.
+pub(crate) fn truncate(
+    text: &mut String,
+    max_len: usize,
+    ellipsis: &str,
+    parse_mode: Option<ParseMode>,
+) {
+    text::truncate_utf16_with(text, max_len, ellipsis, |text, end| {
+        let kept = &text[..end];
+        match parse_mode {
+            None => end,
+            // Cuts before the '&' of an unterminated entity.
+            Some(ParseMode::Html) => match kept.rfind('&') {
+                Some(amp) if !kept[amp..].contains(';') && text[amp..].contains(';') => amp,
+                _ => end,
+            },
+            // An odd number of trailing '\' would escape the ellipsis.
+            Some(ParseMode::MarkdownV2) => {
+                let backslashes = kept.len() - kept.trim_end_matches('\\').len();
+                end - backslashes % 2
+            }
+        }
+    });
+}
+
+fn escape_with(text: &str, needs_escape: impl Fn(char) -> bool) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if needs_escape(ch) {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+    }
+    escaped
+}
+
+// Wraps the text in a code block, returns the parse mode the wrapped text must
+// be sent with.
+//
+// Plain text is wrapped in an HTML `<pre>` block, a ```` ``` ```` fence around
+// it is converted, as it would be sent literally otherwise. Already wrapped
+// markup is returned unchanged.
+pub(crate) fn code_block(
+    text: String,
+    parse_mode: Option<ParseMode>,
+) -> (String, Option<ParseMode>) {
+    match parse_mode {
+        None => {
+            let wrapped = match split_fence(&text) {
+                Some(("", code)) => format!("<pre>{}</pre>", escape_html(code)),
+                Some((language, code)) => format!(
+                    "<pre><code class=\"language-{language}\">{}</code></pre>",
+                    escape_html(code)
+                ),
+                None => format!("<pre>{}</pre>", escape_html(&text)),
+            };
+            (wrapped, Some(ParseMode::Html))
+        }
+        Some(ParseMode::Html) => {
+            let trimmed = text.trim();
+            if trimmed.starts_with("<pre") && trimmed.ends_with("</pre>") {
+                return (text, parse_mode);
+            }
+            (format!("<pre>{}</pre>", escape_html(&text)), parse_mode)
+        }
+        Some(ParseMode::MarkdownV2) => {
+            if split_fence(&text).is_some() {
+                return (text, parse_mode);
+            }
+            // Inside `pre` entities, only '`' and '\' need to be escaped.
+            let escaped = escape_with(&text, |ch| matches!(ch, '`' | '\\'));
+            (format!("```\n{escaped}\n```"), parse_mode)
+        }
+    }
+}
+
+// Wraps the text in an expandable blockquote, returns the parse mode the
+// wrapped text must be sent with.
+//
+// Texts with a parse mode are already markup, so they are wrapped as-is. Plain
+// text is escaped and wrapped in HTML.
+pub(crate) fn expandable_blockquote(
+    text: String,
+    parse_mode: Option<ParseMode>,
+) -> (String, Option<ParseMode>) {
+    match parse_mode {
+        None => (
+            format!("<blockquote expandable>{}</blockquote>", escape_html(&text)),
+            Some(ParseMode::Html),
+        ),
+        Some(ParseMode::Html) => (
+            format!("<blockquote expandable>{text}</blockquote>"),
+            parse_mode,
+        ),
+        Some(ParseMode::MarkdownV2) => {
+            // Every line is prefixed with '>', the first one with '**>', and the
+            // last one is suffixed with '||'.
+            let mut quoted = String::with_capacity(text.len() + 8);
+            for (i, line) in text.split('\n').enumerate() {
+                quoted.push_str(if i == 0 { "**>" } else { "\n>" });
+                quoted.push_str(line);
+            }
+            quoted.push_str("||");
+            (quoted, parse_mode)
+        }
+    }
+}
+
+// Splits text fenced with ```` ``` ```` into the language, empty if none, and
+// the code inside.
+fn split_fence(text: &str) -> Option<(&str, &str)> {
+    let inner = text.trim().strip_prefix("```")?.strip_suffix("```")?;
+    match inner.split_once('\n') {
+        Some((language, code))
+            if language
+                .chars()
+                .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '+' | '-' | '_' | '#')) =>
+        {
+            Some((language, code.strip_suffix('\n').unwrap_or(code)))
+        }
+        _ => Some(("", inner)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_html() {
+        assert_eq!(
+            super::escape_html("a < b && c > d"),
+            "a &lt; b &amp;&amp; c &gt; d"
+        );
+        assert_eq!(super::escape_html("&lt;"), "&amp;lt;");
+        assert_eq!(super::escape_html("\"quoted\" 'text'"), "\"quoted\" 'text'");
+        // Characters outside the BMP are kept as-is, next to entities
+        assert_eq!(super::escape_html("😀<&>🏳️‍🌈"), "😀&lt;&amp;&gt;🏳️‍🌈");
+    }
+
+    #[test]
+    fn escape_then_truncate() {
+        let truncated = |text: &str, max_len, parse_mode| {
+            let mut text = text.to_string();
+            super::truncate(&mut text, max_len, "…", parse_mode);
+            text
+        };
+
+        // Every cut of escaped text keeps whole characters and whole entities.
+        let escaped = super::escape_html("😀<😀&a>😀 b&c");
+        for max_len in 0..=text::utf16_len(&escaped) + 1 {
+            let text = truncated(&escaped, max_len, Some(ParseMode::Html));
+            assert!(text::utf16_len(&text) <= max_len, "{text:?}");
+            let kept = text.strip_suffix('…').unwrap_or(&text);
+            assert!(escaped.starts_with(kept), "{text:?}");
+            assert!(
+                kept.rfind('&').is_none_or(|amp| kept[amp..].contains(';')),
+                "{text:?}"
+            );
+        }
+        assert_eq!(truncated(&escaped, 5, Some(ParseMode::Html)), "😀…");
+        assert_eq!(truncated(&escaped, 7, Some(ParseMode::Html)), "😀&lt;…");
+        // Plain text has no entities, so it's cut anywhere
+        assert_eq!(truncated(&escaped, 5, None), "😀&l…");
+
+        let escaped = escape("😀_😀*", Some(ParseMode::MarkdownV2));
+        assert_eq!(escaped, "😀\\_😀\\*");
+        assert_eq!(truncated(&escaped, 4, Some(ParseMode::MarkdownV2)), "😀…");
+        assert_eq!(
+            truncated(&escaped, 5, Some(ParseMode::MarkdownV2)),
+            "😀\\_…"
+        );
+        assert_eq!(
+            truncated(&escaped, 7, Some(ParseMode::MarkdownV2)),
+            "😀\\_😀…"
+        );
+    }
+
+    #[test]
+    fn code_block() {
+        let text = "panicked at 'a < b && c', src/main.rs:1:1\nstack backtrace:";
+        assert_eq!(
+            super::code_block(text.into(), None),
+            (
+                "<pre>panicked at 'a &lt; b &amp;&amp; c', src/main.rs:1:1\nstack backtrace:</pre>"
+                    .into(),
+                Some(ParseMode::Html)
+            )
+        );
+        assert_eq!(
+            super::code_block("a\\b\n`c`".into(), Some(ParseMode::MarkdownV2)),
+            (
+                "```\na\\\\b\n\\`c\\`\n```".into(),
+                Some(ParseMode::MarkdownV2)
+            )
+        );
+
+        // No double wrapping
+        for (text, parse_mode) in [
+            ("<pre>a\nb</pre>", Some(ParseMode::Html)),
+            ("```\na\nb\n```", Some(ParseMode::MarkdownV2)),
+        ] {
+            assert_eq!(
+                super::code_block(text.into(), parse_mode),
+                (text.into(), parse_mode)
+            );
+        }
+
+        // Fences in plain text would be sent literally, so they are converted
+        for (text, expected) in [
+            ("```\na < b\nc\n```", "<pre>a &lt; b\nc</pre>"),
+            (
+                "```rust\nfn main() {}\n```",
+                "<pre><code class=\"language-rust\">fn main() {}</code></pre>",
+            ),
+            ("```a & b\nc```", "<pre>a &amp; b\nc</pre>"),
+        ] {
+            assert_eq!(
+                super::code_block(text.into(), None),
+                (expected.into(), Some(ParseMode::Html))
+            );
+        }
+    }
+
+    #[test]
+    fn expandable_blockquote() {
+        assert_eq!(
+            super::expandable_blockquote("a < b\nc".into(), None),
+            (
+                "<blockquote expandable>a &lt; b\nc</blockquote>".into(),
+                Some(ParseMode::Html)
+            )
+        );
+        assert_eq!(
+            super::expandable_blockquote("<b>a</b>\nc".into(), Some(ParseMode::Html)),
+            (
+                "<blockquote expandable><b>a</b>\nc</blockquote>".into(),
+                Some(ParseMode::Html)
+            )
+        );
+        assert_eq!(
+            super::expandable_blockquote("*a* \\> b\n\nc".into(), Some(ParseMode::MarkdownV2)),
+            ("**>*a* \\> b\n>\n>c||".into(), Some(ParseMode::MarkdownV2))
+        );
+    }
+}
@@ -0,0 +1,72 @@
+use spdlog::Level;
+
+/// Represents the behavior when the formatted text of a log is empty or
+/// whitespace-only.
+///
+/// Telegram rejects messages with empty text, so they cannot be sent as-is.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum EmptyPolicy {
+    /// Skips sending the log silently.
+    #[default]
+    Skip,
+    /// Sends the given placeholder text instead.
+    Placeholder(String),
+    /// Returns [`Error::EmptyText`] to the error handler.
+    ///
+    /// [`Error::EmptyText`]: crate::Error::EmptyText
+    Error,
+}
+
+/// Represents the behavior when the recipient user has blocked the bot.
+///
+/// Once a user blocks the bot, all subsequent sends to the user fail until the
+/// user unblocks it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BlockedPolicy {
+    /// Stops sending logs, returns [`Error::BotBlocked`] to the error handler
+    /// only once.
+    ///
+    /// [`Error::BotBlocked`]: crate::Error::BotBlocked
+    Disable,
+    /// Keeps sending logs, ignores the errors.
+    Ignore,
+    /// Keeps sending logs, returns [`Error::BotBlocked`] to the error handler
+    /// every time.
+    ///
+    /// [`Error::BotBlocked`]: crate::Error::BotBlocked
+    #[default]
+    Error,
+}
+
+/// Represents which logs to send and which of them to notify with sound.
+///
+/// This is a combination of [`TelegramSinkBuilder::level_filter`] and
+/// [`TelegramSinkBuilder::silence`] with explicit semantics, see
+/// [`TelegramSinkBuilder::notify_policy`].
+///
+/// ## Examples
+///
+/// ```
+/// use spdlog::Level;
+/// use spdlog_telegram::NotifyPolicy;
+///
+/// // Sends logs with level: critical, error, warn.
+/// // Notifies with sound for logs with level: critical, error.
+/// let policy = NotifyPolicy {
+///     min_level_to_send: Level::Warn,
+///     min_level_for_sound: Level::Error,
+/// };
+/// ```
+///
+/// [`TelegramSinkBuilder::level_filter`]: crate::TelegramSinkBuilder::level_filter
+/// [`TelegramSinkBuilder::silence`]: crate::TelegramSinkBuilder::silence
+/// [`TelegramSinkBuilder::notify_policy`]: crate::TelegramSinkBuilder::notify_policy
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NotifyPolicy {
+    /// The least severe level of logs to be sent.
+    pub min_level_to_send: Level,
+    /// The least severe level of logs to be notified with sound.
+    ///
+    /// Logs less severe than this are sent without notification sound.
+    pub min_level_for_sound: Level,
+}
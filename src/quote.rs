@@ -0,0 +1,175 @@
+use serde_json as json;
+
+use crate::text;
+
+/// Represents a quote of the message being replied to.
+///
+/// Used to reply to a message with a quote, via `reply_to_with_quote` of
+/// [`Recipient::builder`]. See [Telegram Bot API: ReplyParameters][api].
+///
+/// Telegram requires the quote to be an exact substring of the replied
+/// message, 0-1024 characters after entities parsing. The position and entity
+/// offsets are measured in UTF-16 code units, as everywhere in the Bot API.
+///
+/// ## Examples
+///
+/// ```
+/// use serde_json::json;
+/// use spdlog_telegram::Quote;
+///
+/// let alert = "#log #error disk usage 95% on web-3";
+/// let quote = Quote::new("web-3")
+///     .position_at_byte(alert, alert.find("web-3").unwrap())
+///     .entities(vec![json!({ "type": "bold", "offset": 0, "length": 5 })]);
+/// ```
+///
+/// [`Recipient::builder`]: crate::Recipient::builder
+/// [api]: https://core.telegram.org/bots/api#replyparameters
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Quote {
+    text: String,
+    position: Option<usize>,
+    entities: Vec<json::Value>,
+}
+
+impl Quote {
+    /// Constructs a `Quote` with the quoted text.
+    pub fn new<S>(text: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            text: text.into(),
+            position: None,
+            entities: vec![],
+        }
+    }
+
+    /// Specifies the position of the quote in the replied message, in UTF-16
+    /// code units.
+    ///
+    /// Required if the quoted text appears more than once in the message.
+    #[must_use]
+    pub fn position(mut self, position: usize) -> Self {
+        self.position = Some(position);
+        self
+    }
+
+    /// Specifies the position of the quote in the replied message by a byte
+    /// offset into the message text, converting it to UTF-16 code units.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset is not on a `char` boundary of the message
+    /// text, or is out of bounds.
+    #[must_use]
+    pub fn position_at_byte(self, message_text: &str, byte_offset: usize) -> Self {
+        self.position(text::utf16_len(&message_text[..byte_offset]))
+    }
+
+    /// Specifies the [message entities] of the quote, e.g. bold or italic
+    /// text, with offsets relative to the quote.
+    ///
+    /// [message entities]: https://core.telegram.org/bots/api#messageentity
+    #[must_use]
+    pub fn entities(mut self, entities: Vec<json::Value>) -> Self {
+        self.entities = entities;
+        self
+    }
+
+    // Writes the quote fields into `reply_parameters`.
+    pub(crate) fn write_to(&self, reply_parameters: &mut json::Map<String, json::Value>) {
+        reply_parameters.insert("quote".into(), self.text.clone().into());
+        if let Some(position) = self.position {
+            reply_parameters.insert("quote_position".into(), position.into());
+        }
+        if !self.entities.is_empty() {
+            reply_parameters.insert("quote_entities".into(), self.entities.clone().into());
+        }
+    }
+}
+
+// Represented as `{"text": ..., "position": ..., "entities": [...]}`, where
+// only `text` is required.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Quote {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut quote = json::Map::new();
+        quote.insert("text".into(), self.text.clone().into());
+        if let Some(position) = self.position {
+            quote.insert("position".into(), position.into());
+        }
+        if !self.entities.is_empty() {
+            quote.insert("entities".into(), self.entities.clone().into());
+        }
+        serde::Serialize::serialize(&quote, serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Quote {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        use serde::de::Error as _;
+
+        let mut quote = <json::Map<String, json::Value>>::deserialize(deserializer)?;
+        let text = match quote.remove("text") {
+            Some(json::Value::String(text)) => text,
+            Some(_) => return Err(D::Error::custom("invalid quote `text`")),
+            None => return Err(D::Error::missing_field("text")),
+        };
+        let position = quote
+            .remove("position")
+            .map(|position| {
+                position
+                    .as_u64()
+                    .and_then(|position| usize::try_from(position).ok())
+                    .ok_or_else(|| D::Error::custom("invalid quote `position`"))
+            })
+            .transpose()?;
+        let entities = match quote.remove("entities") {
+            Some(json::Value::Array(entities)) => entities,
+            Some(_) => return Err(D::Error::custom("invalid quote `entities`")),
+            None => vec![],
+        };
+        if let Some(key) = quote.keys().next() {
+            return Err(D::Error::unknown_field(
+                key,
+                &["text", "position", "entities"],
+            ));
+        }
+        Ok(Self {
+            text,
+            position,
+            entities,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn position_at_byte() {
+        // '😀' is 4 bytes in UTF-8, but 2 code units in UTF-16.
+        let message = "😀 alert: disk full";
+        let quote =
+            Quote::new("disk full").position_at_byte(message, message.find("disk").unwrap());
+        assert_eq!(quote.position, Some(10));
+
+        let mut reply_parameters = json::Map::new();
+        quote.write_to(&mut reply_parameters);
+        assert_eq!(
+            json::Value::Object(reply_parameters),
+            json!({ "quote": "disk full", "quote_position": 10 })
+        );
+    }
+}
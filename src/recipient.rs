@@ -1,6 +1,9 @@
-use std::{borrow::Cow, convert::Infallible};
+use std::{borrow::Cow, convert::Infallible, num::TryFromIntError};
 
 use serde_json as json;
+use url::Url;
+
+use crate::{Error, Quote, Result};
 
 #[derive(Debug, PartialEq, Eq)]
 enum TargetChatInner {
@@ -31,19 +34,83 @@ pub(crate) mod __private {
                 TargetChatInner::Username(username) => json::Value::String(username),
             }
         }
+
+        // Returns the ID if it's a user ID, i.e. a private chat.
+        pub(crate) fn user_id(&self) -> Option<i64> {
+            match self.0 {
+                TargetChatInner::Id(id) if id > 0 => Some(id),
+                _ => None,
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        pub(crate) fn clone_json(&self) -> json::Value {
+            match &self.0 {
+                TargetChatInner::Id(id) => json::Value::Number((*id).into()),
+                TargetChatInner::Username(username) => json::Value::String(username.clone()),
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        pub(crate) fn is_id(&self) -> bool {
+            matches!(self.0, TargetChatInner::Id(_))
+        }
     }
 }
 use __private::TargetChat;
 
+// The message to reply to.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Reply {
+    pub(crate) message_id: u64,
+    // `None` for the recipient chat
+    pub(crate) chat: Option<TargetChat>,
+    pub(crate) quote: Option<Quote>,
+}
+
+impl Reply {
+    fn new(message_id: u64, chat: Option<TargetChat>) -> Self {
+        Self {
+            message_id,
+            chat,
+            quote: None,
+        }
+    }
+}
+
 /// Represents a Telegram chat recipient.
 ///
 /// Not just a chat ID or username, it can also be represented with a message
 /// thread ID or reply.
+///
+/// Chat IDs of other integer types than `i64` can be converted with
+/// `TryFrom`, e.g. a `u64` from a JSON library. There's intentionally no
+/// `From<i32>`, as it would make integer literals such as `-1001234567890`
+/// ambiguous, use `i64::from` for `i32` chat IDs.
+///
+/// With crate feature `serde`, it implements `Serialize` and `Deserialize`,
+/// e.g. to be loaded from a config file. A plain recipient is represented as
+/// a bare chat ID or username, others as an object:
+///
+/// ```json
+/// {
+///     "username": "@my_chat",
+///     "thread_id": 114,
+///     "reply_to": { "message_id": 514, "quote": { "text": "web-3" } }
+/// }
+/// ```
+///
+/// `chat_id` can be used instead of `username`, and `reply_to` can be a bare
+/// message ID if there's no quote. Set `"linked_discussion": true` to send to
+/// the discussion group linked to the channel, see
+/// [`Recipient::linked_discussion`]. Unknown fields are rejected.
 #[derive(Debug, PartialEq, Eq)]
 pub struct Recipient {
     pub(crate) target: TargetChat,
     pub(crate) thread_id: Option<u64>,
-    pub(crate) reply_to: Option<(u64, Option<TargetChat>)>,
+    pub(crate) reply_to: Option<Reply>,
+    // The target is a channel, logs are sent to its linked discussion group
+    pub(crate) linked_discussion: bool,
 }
 
 impl Recipient {
@@ -53,6 +120,7 @@ impl Recipient {
             target: (),
             thread_id: None,
             reply_to: None,
+            linked_discussion: false,
         }
     }
 
@@ -63,6 +131,20 @@ impl Recipient {
         Self::builder().chat_id(chat_id).build()
     }
 
+    /// Constructs a `Recipient` from a user ID, i.e. a private chat with the
+    /// user.
+    ///
+    /// This is also the way to log to yourself. Bots can't post to the
+    /// "Saved Messages" of a user, logs go to the chat of the user with the
+    /// bot instead, so the user must have started the bot first.
+    ///
+    /// This is equivalent to `Recipient::builder().user_id(user_id)?.build()`.
+    /// Returns an error if the user ID is greater than `i64::MAX`, which is
+    /// never a valid user ID.
+    pub fn user_id(user_id: u64) -> std::result::Result<Self, TryFromIntError> {
+        Ok(Self::builder().user_id(user_id)?.build())
+    }
+
     /// Constructs a `Recipient` from a username.
     ///
     /// This is equivalent to `Recipient::builder().username(username).build()`.
@@ -72,6 +154,44 @@ impl Recipient {
     {
         Self::builder().username(username).build()
     }
+
+    /// Constructs a `Recipient` for the discussion group linked to the channel.
+    ///
+    /// This allows people to reply to alerts, which is not possible in the
+    /// channel itself. The linked group is looked up via [`getChat`] when
+    /// building the sink, which fails with [`Error::NoLinkedDiscussion`] if
+    /// the channel has none.
+    ///
+    /// This is equivalent to
+    /// `Recipient::builder().chat_id(channel_id).linked_discussion().build()`.
+    ///
+    /// [`getChat`]: https://core.telegram.org/bots/api#getchat
+    pub fn linked_discussion(channel_id: i64) -> Self {
+        Self::builder()
+            .chat_id(channel_id)
+            .linked_discussion()
+            .build()
+    }
+}
+
+impl Recipient {
+    // Returns a warning if a user ID is used as a channel or a group, i.e. with a
+    // linked discussion group or a forum topic to create.
+    pub(crate) fn check_target(&self, creates_topic: bool) -> Option<UserIdAsChannel> {
+        let chat_id = self.target.user_id()?;
+        (self.linked_discussion || creates_topic).then_some(UserIdAsChannel { chat_id })
+    }
+}
+
+// Reported to the error handler as a warning when building the sink, as a
+// positive chat ID of a channel or a group is almost always a mistake.
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+#[error(
+    "recipient {chat_id} is a user ID, but it's used as a channel or group, chat IDs of \
+     channels and groups are negative"
+)]
+pub(crate) struct UserIdAsChannel {
+    pub(crate) chat_id: i64,
 }
 
 impl From<i64> for Recipient {
@@ -80,12 +200,44 @@ impl From<i64> for Recipient {
     }
 }
 
+macro_rules! impl_try_from_int_for_recipient {
+    ( $($int_ty:ty),+ ) => {
+        $(/// Constructs a `Recipient` from a chat ID, failing if it's out of the
+        /// range of `i64`.
+        impl TryFrom<$int_ty> for Recipient {
+            type Error = TryFromIntError;
+
+            fn try_from(chat_id: $int_ty) -> std::result::Result<Self, Self::Error> {
+                i64::try_from(chat_id).map(Self::chat_id)
+            }
+        })+
+    };
+}
+impl_try_from_int_for_recipient!(u64, i128);
+
+/// Constructs a `Recipient` from a chat ID and a message thread ID.
+impl From<(i64, u64)> for Recipient {
+    fn from((chat_id, thread_id): (i64, u64)) -> Self {
+        Self::builder()
+            .chat_id(chat_id)
+            .thread_id(thread_id)
+            .build()
+    }
+}
+
 macro_rules! impl_from_str_for_recipient {
     ( $($str_ty:ty),+ ) => {
         $(impl From<$str_ty> for Recipient {
             fn from(username: $str_ty) -> Self {
                 Self::username(username)
             }
+        }
+
+        /// Constructs a `Recipient` from a username and a message thread ID.
+        impl From<($str_ty, u64)> for Recipient {
+            fn from((username, thread_id): ($str_ty, u64)) -> Self {
+                Self::builder().username(username).thread_id(thread_id).build()
+            }
         })+
     };
 }
@@ -94,7 +246,8 @@ impl_from_str_for_recipient!(&str, &mut str, Box<str>, Cow<'_, str>, String, &St
 pub struct RecipientBuilder<ArgC> {
     target: ArgC,
     thread_id: Option<u64>,
-    reply_to: Option<(u64, Option<TargetChat>)>,
+    reply_to: Option<Reply>,
+    linked_discussion: bool,
 }
 
 impl<ArgC> RecipientBuilder<ArgC> {
@@ -103,9 +256,25 @@ impl<ArgC> RecipientBuilder<ArgC> {
             target: TargetChat::id(chat_id),
             thread_id: self.thread_id,
             reply_to: self.reply_to,
+            linked_discussion: self.linked_discussion,
         }
     }
 
+    /// Specifies the recipient to be a private chat with the user.
+    ///
+    /// User IDs are always positive, unlike chat IDs of groups and channels
+    /// (e.g. `-1001234567890`), so taking a `u64` guards against mixing them
+    /// up. The user must have started a conversation with the bot.
+    ///
+    /// Returns an error if the user ID is greater than `i64::MAX`, which is
+    /// never a valid user ID.
+    pub fn user_id(
+        self,
+        user_id: u64,
+    ) -> std::result::Result<RecipientBuilder<TargetChat>, TryFromIntError> {
+        Ok(self.chat_id(i64::try_from(user_id)?))
+    }
+
     pub fn username<S>(self, username: S) -> RecipientBuilder<TargetChat>
     where
         S: Into<String>,
@@ -114,6 +283,7 @@ impl<ArgC> RecipientBuilder<ArgC> {
             target: TargetChat::username(username.into()),
             thread_id: self.thread_id,
             reply_to: self.reply_to,
+            linked_discussion: self.linked_discussion,
         }
     }
 
@@ -122,28 +292,131 @@ impl<ArgC> RecipientBuilder<ArgC> {
         self
     }
 
-    pub fn reply_to(mut self, message_id: u64) -> Self {
-        self.reply_to = Some((message_id, None));
+    /// Specifies the message thread ID, i.e. the forum topic to send to, from
+    /// any integer type, e.g. an `i64` from tooling representing IDs as signed
+    /// integers.
+    ///
+    /// This avoids manual casts that could silently wrap. Returns an error if
+    /// the thread ID is negative or doesn't fit in a `u64`, which is never a
+    /// valid thread ID.
+    pub fn try_thread_id<T>(self, thread_id: T) -> std::result::Result<Self, T::Error>
+    where
+        T: TryInto<u64>,
+    {
+        Ok(self.thread_id(thread_id.try_into()?))
+    }
+
+    /// Specifies the target chat to be a channel, whose linked discussion group
+    /// is the actual recipient.
+    ///
+    /// See [`Recipient::linked_discussion`]. The thread ID, if any, applies to
+    /// the group.
+    pub fn linked_discussion(mut self) -> Self {
+        self.linked_discussion = true;
         self
     }
 
-    // It's not a very good name, and considering there's almost no use case for it,
-    // I chose not to make it public for now.
-    #[allow(dead_code)]
-    fn reply_to_diff_chat_id(mut self, message_id: u64, chat_id: i64) -> Self {
-        self.reply_to = Some((message_id, Some(TargetChat::id(chat_id))));
+    /// Replies to the message with the given ID in the recipient chat.
+    ///
+    /// Logs are sent as replies to a single message, e.g. an incident or the
+    /// message of a user that triggered a moderation action, so that they
+    /// notify its author and are grouped under it. The message is specified
+    /// with one of:
+    ///
+    /// - [`RecipientBuilder::reply_to`] for a message in the recipient chat.
+    /// - [`RecipientBuilder::reply_to_with_quote`] for the same, quoting a part
+    ///   of it.
+    /// - [`RecipientBuilder::reply_to_link`] for a message in any chat, e.g.
+    ///   another chat than the recipient.
+    ///
+    /// A later call replaces the earlier one. Telegram rejects replies to
+    /// messages that are not found, e.g. deleted. Enable
+    /// [`TelegramSinkBuilder::allow_sending_without_reply`] to send the logs
+    /// as regular messages instead.
+    ///
+    /// The reply is independent of the [thread ID], the message can be in any
+    /// thread of a forum, e.g. in the General topic while logging to another
+    /// topic. Both are sent as-is, and Telegram places the reply in the
+    /// recipient thread. If Telegram rejects replying to a message in another
+    /// topic of the chat, specify its chat explicitly with
+    /// [`RecipientBuilder::reply_to_link`], so that it's sent as a reply to a
+    /// message in another chat or topic.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use spdlog_telegram::{Recipient, TelegramSink};
+    ///
+    /// let recipient = Recipient::builder()
+    ///     .chat_id(-1001234567890)
+    ///     .reply_to(514)
+    ///     .build();
+    /// TelegramSink::builder()
+    ///     .recipient(recipient)
+    ///     // The message may be deleted by then
+    ///     .allow_sending_without_reply(true);
+    /// ```
+    ///
+    /// [thread ID]: RecipientBuilder::thread_id
+    /// [`TelegramSinkBuilder::allow_sending_without_reply`]: crate::TelegramSinkBuilder::allow_sending_without_reply
+    pub fn reply_to(mut self, message_id: u64) -> Self {
+        self.reply_to = Some(Reply::new(message_id, None));
         self
     }
 
-    // Same as above.
-    #[allow(dead_code)]
-    fn reply_to_diff_username<S>(mut self, message_id: u64, chat_username: S) -> Self
-    where
-        S: Into<String>,
-    {
-        self.reply_to = Some((message_id, Some(TargetChat::username(chat_username.into()))));
+    /// Replies to the message with the given ID in the recipient chat, quoting
+    /// a part of it.
+    ///
+    /// See [`RecipientBuilder::reply_to`], and [`Quote`] for the constraints of
+    /// quotes.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use spdlog_telegram::{Quote, Recipient};
+    ///
+    /// // The message reads "Deploying to web-3"
+    /// Recipient::builder()
+    ///     .chat_id(-1001234567890)
+    ///     .reply_to_with_quote(514, Quote::new("web-3").position(13))
+    ///     .build();
+    /// ```
+    pub fn reply_to_with_quote(mut self, message_id: u64, quote: Quote) -> Self {
+        self.reply_to = Some(Reply {
+            quote: Some(quote),
+            ..Reply::new(message_id, None)
+        });
         self
     }
+
+    /// Replies to the message of the given link, e.g. `https://t.me/c/123/456`
+    /// or `https://t.me/my_channel/456`.
+    ///
+    /// Both links for private chats (`/c/<id>/...`) and public chats
+    /// (`/<username>/...`) are supported. The chat in the link is always
+    /// specified in `reply_parameters`, so the message can be in a chat other
+    /// than the recipient. See [`RecipientBuilder::reply_to`].
+    ///
+    /// Returns [`Error::ParseMessageLink`] if the link is invalid.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use spdlog_telegram::Recipient;
+    ///
+    /// // Logs go to the on-call chat, replying to the incident in the status channel
+    /// Recipient::builder()
+    ///     .username("@my_oncall")
+    ///     .reply_to_link("https://t.me/my_status/456")?
+    ///     .build();
+    /// # Ok::<(), spdlog_telegram::Error>(())
+    /// ```
+    pub fn reply_to_link(mut self, link: &str) -> Result<Self> {
+        let (message_id, chat) =
+            parse_message_link(link).ok_or_else(|| Error::ParseMessageLink(link.to_string()))?;
+        self.reply_to = Some(Reply::new(message_id, Some(chat)));
+        Ok(self)
+    }
 }
 
 impl RecipientBuilder<()> {
@@ -161,10 +434,219 @@ impl RecipientBuilder<TargetChat> {
             target: self.target,
             thread_id: self.thread_id,
             reply_to: self.reply_to,
+            linked_discussion: self.linked_discussion,
         }
     }
 }
 
+// Parses `https://t.me/<username>/[<thread_id>/]<message_id>` or
+// `https://t.me/c/<internal_id>/[<thread_id>/]<message_id>`.
+fn parse_message_link(link: &str) -> Option<(u64, TargetChat)> {
+    let url = if link.contains("://") {
+        Url::parse(link)
+    } else {
+        Url::parse(&format!("https://{link}"))
+    }
+    .ok()?;
+    if !matches!(url.host_str()?, "t.me" | "telegram.me") {
+        return None;
+    }
+
+    let segments = url.path_segments()?.collect::<Vec<_>>();
+    let (chat, rest) = match segments.as_slice() {
+        ["c", internal_id, rest @ ..] => {
+            // Private links use the chat ID without the `-100` prefix.
+            let internal_id = internal_id.parse::<i64>().ok().filter(|id| *id > 0)?;
+            let chat_id = (-1_000_000_000_000_i64).checked_sub(internal_id)?;
+            (TargetChat::id(chat_id), rest)
+        }
+        [username, rest @ ..] if !username.is_empty() => {
+            (TargetChat::username(format!("@{username}")), rest)
+        }
+        _ => return None,
+    };
+    let message_id = match rest {
+        [message_id] | [_, message_id] => message_id.parse().ok()?,
+        _ => return None,
+    };
+    Some((message_id, chat))
+}
+
+// Plain recipients are represented as a bare chat ID or username, others as an
+// object, e.g. `{"username": "@my_chat", "thread_id": 114}`. A reply without a
+// chat or quote is represented as a bare message ID, e.g. `"reply_to": 514`.
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use serde::{
+        Deserialize, Deserializer, Serialize, Serializer, de::Error as _, ser::Error as _,
+    };
+
+    use super::*;
+
+    type Map = json::Map<String, json::Value>;
+
+    const FIELDS: &[&str] = &[
+        "chat_id",
+        "username",
+        "thread_id",
+        "reply_to",
+        "linked_discussion",
+    ];
+    const REPLY_FIELDS: &[&str] = &["message_id", "chat_id", "username", "quote"];
+
+    impl Serialize for Recipient {
+        fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            if self.thread_id.is_none() && self.reply_to.is_none() && !self.linked_discussion {
+                return self.target.clone_json().serialize(serializer);
+            }
+            let mut recipient = Map::new();
+            write_target(&self.target, &mut recipient);
+            if let Some(thread_id) = self.thread_id {
+                recipient.insert("thread_id".into(), thread_id.into());
+            }
+            if let Some(reply) = &self.reply_to {
+                recipient.insert(
+                    "reply_to".into(),
+                    reply_to_json(reply).map_err(S::Error::custom)?,
+                );
+            }
+            if self.linked_discussion {
+                recipient.insert("linked_discussion".into(), true.into());
+            }
+            recipient.serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Recipient {
+        fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let mut recipient = match json::Value::deserialize(deserializer)? {
+                json::Value::Object(recipient) => recipient,
+                target => {
+                    return Ok(Self {
+                        target: target_from_json(target).map_err(D::Error::custom)?,
+                        thread_id: None,
+                        reply_to: None,
+                        linked_discussion: false,
+                    });
+                }
+            };
+            let target = take_target(&mut recipient)
+                .map_err(D::Error::custom)?
+                .ok_or_else(|| D::Error::custom("missing field `chat_id` or `username`"))?;
+            let thread_id = recipient
+                .remove("thread_id")
+                .map(|thread_id| {
+                    thread_id
+                        .as_u64()
+                        .ok_or_else(|| D::Error::custom("invalid `thread_id`"))
+                })
+                .transpose()?;
+            let reply_to = recipient
+                .remove("reply_to")
+                .map(reply_from_json)
+                .transpose()
+                .map_err(D::Error::custom)?;
+            let linked_discussion = match recipient.remove("linked_discussion") {
+                Some(json::Value::Bool(linked_discussion)) => linked_discussion,
+                Some(_) => return Err(D::Error::custom("invalid `linked_discussion`")),
+                None => false,
+            };
+            if let Some(key) = recipient.keys().next() {
+                return Err(D::Error::unknown_field(key, FIELDS));
+            }
+            Ok(Self {
+                target,
+                thread_id,
+                reply_to,
+                linked_discussion,
+            })
+        }
+    }
+
+    fn write_target(target: &TargetChat, map: &mut Map) {
+        let key = if target.is_id() {
+            "chat_id"
+        } else {
+            "username"
+        };
+        map.insert(key.into(), target.clone_json());
+    }
+
+    fn target_from_json(target: json::Value) -> std::result::Result<TargetChat, String> {
+        match target {
+            json::Value::Number(id) => id
+                .as_i64()
+                .map(TargetChat::id)
+                .ok_or_else(|| format!("invalid chat ID `{id}`")),
+            json::Value::String(username) => Ok(TargetChat::username(username)),
+            target => Err(format!("invalid recipient `{target}`")),
+        }
+    }
+
+    fn take_target(map: &mut Map) -> std::result::Result<Option<TargetChat>, String> {
+        match (map.remove("chat_id"), map.remove("username")) {
+            (Some(_), Some(_)) => Err("`chat_id` and `username` are mutually exclusive".into()),
+            (Some(json::Value::String(id)), None) => Err(format!("invalid chat ID `{id:?}`")),
+            (Some(target), None) | (None, Some(target @ json::Value::String(_))) => {
+                target_from_json(target).map(Some)
+            }
+            (None, Some(username)) => Err(format!("invalid username `{username}`")),
+            (None, None) => Ok(None),
+        }
+    }
+
+    fn reply_to_json(reply: &Reply) -> json::Result<json::Value> {
+        if reply.chat.is_none() && reply.quote.is_none() {
+            return Ok(reply.message_id.into());
+        }
+        let mut reply_to = Map::new();
+        reply_to.insert("message_id".into(), reply.message_id.into());
+        if let Some(chat) = &reply.chat {
+            write_target(chat, &mut reply_to);
+        }
+        if let Some(quote) = &reply.quote {
+            reply_to.insert("quote".into(), json::to_value(quote)?);
+        }
+        Ok(reply_to.into())
+    }
+
+    fn reply_from_json(reply_to: json::Value) -> std::result::Result<Reply, String> {
+        let mut reply_to = match reply_to {
+            json::Value::Object(reply_to) => reply_to,
+            message_id => {
+                return message_id
+                    .as_u64()
+                    .map(|message_id| Reply::new(message_id, None))
+                    .ok_or_else(|| format!("invalid `reply_to` `{message_id}`"));
+            }
+        };
+        let message_id = reply_to
+            .remove("message_id")
+            .ok_or("missing field `message_id` of `reply_to`")?
+            .as_u64()
+            .ok_or("invalid `message_id` of `reply_to`")?;
+        let chat = take_target(&mut reply_to)?;
+        let quote = reply_to
+            .remove("quote")
+            .map(|quote| Quote::deserialize(quote).map_err(|err| err.to_string()))
+            .transpose()?;
+        if let Some(key) = reply_to.keys().next() {
+            return Err(json::Error::unknown_field(key, REPLY_FIELDS).to_string());
+        }
+        Ok(Reply {
+            message_id,
+            chat,
+            quote,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -176,5 +658,206 @@ mod tests {
         }
         assert_eq!(echo(-1001234567890), Recipient::chat_id(-1001234567890));
         assert_eq!(echo("@username"), Recipient::username("@username"));
+
+        // Identity conversion for generic code producing a `Recipient`
+        assert_eq!(
+            echo(Recipient::chat_id(-1001234567890)),
+            Recipient::chat_id(-1001234567890)
+        );
+
+        let with_thread = |target| Recipient {
+            target,
+            thread_id: Some(114),
+            reply_to: None,
+            linked_discussion: false,
+        };
+        assert_eq!(
+            echo((-1001234567890, 114)),
+            with_thread(TargetChat::id(-1001234567890))
+        );
+        assert_eq!(
+            echo(("@my_chat", 114)),
+            with_thread(TargetChat::username("@my_chat".into()))
+        );
+        assert_eq!(
+            echo((String::from("@my_chat"), 114)),
+            with_thread(TargetChat::username("@my_chat".into()))
+        );
+    }
+
+    #[test]
+    fn from_int() {
+        assert_eq!(
+            Recipient::try_from(123456789_u64).unwrap(),
+            Recipient::chat_id(123456789)
+        );
+        assert_eq!(
+            Recipient::try_from(-1001234567890_i128).unwrap(),
+            Recipient::chat_id(-1001234567890)
+        );
+        assert!(Recipient::try_from(u64::MAX).is_err());
+        assert!(Recipient::try_from(i128::MIN).is_err());
+    }
+
+    #[test]
+    fn user_id() {
+        assert_eq!(
+            Recipient::user_id(123456789).unwrap(),
+            Recipient::chat_id(123456789)
+        );
+        assert_ne!(
+            Recipient::user_id(1234567890).unwrap(),
+            Recipient::chat_id(-1001234567890)
+        );
+        assert_eq!(
+            Recipient::user_id(i64::MAX as u64).unwrap(),
+            Recipient::chat_id(i64::MAX)
+        );
+        assert!(Recipient::user_id(i64::MAX as u64 + 1).is_err());
+        assert!(Recipient::builder().user_id(u64::MAX).is_err());
+    }
+
+    #[test]
+    fn try_thread_id() {
+        let thread_id = |recipient: std::result::Result<RecipientBuilder<TargetChat>, _>| {
+            recipient.map(|r| r.build().thread_id)
+        };
+        let builder = || Recipient::builder().chat_id(-1001234567890);
+        assert_eq!(thread_id(builder().try_thread_id(0_i64)), Ok(Some(0)));
+        assert_eq!(thread_id(builder().try_thread_id(114_i64)), Ok(Some(114)));
+        assert_eq!(
+            thread_id(builder().try_thread_id(i64::MAX)),
+            Ok(Some(i64::MAX as u64))
+        );
+        assert!(builder().try_thread_id(-1_i64).is_err());
+        assert!(builder().try_thread_id(i64::MIN).is_err());
+        assert!(builder().try_thread_id(u128::from(u64::MAX) + 1).is_err());
+    }
+
+    #[test]
+    fn reply_to_link() {
+        let reply_to = |link| {
+            Recipient::builder()
+                .chat_id(-1001234567890)
+                .reply_to_link(link)
+                .map(|b| b.build().reply_to.map(|r| (r.message_id, r.chat)))
+        };
+
+        assert_eq!(
+            reply_to("https://t.me/c/1234567890/456").unwrap(),
+            Some((456, Some(TargetChat::id(-1001234567890))))
+        );
+        assert_eq!(
+            reply_to("https://t.me/c/1234567890/114/456").unwrap(),
+            Some((456, Some(TargetChat::id(-1001234567890))))
+        );
+        assert_eq!(
+            reply_to("https://t.me/my_channel/456").unwrap(),
+            Some((456, Some(TargetChat::username("@my_channel".into()))))
+        );
+        assert_eq!(
+            reply_to("t.me/my_channel/456?single").unwrap(),
+            Some((456, Some(TargetChat::username("@my_channel".into()))))
+        );
+
+        for invalid in [
+            "https://t.me/my_channel",
+            "https://t.me/c/1234567890",
+            "https://t.me/c/abc/456",
+            "https://t.me/my_channel/abc",
+            "https://example.com/my_channel/456",
+            "not a link",
+        ] {
+            assert!(
+                matches!(reply_to(invalid), Err(Error::ParseMessageLink(_))),
+                "{invalid}"
+            );
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let round_trip = |recipient: &Recipient, expected: json::Value| {
+            let serialized = json::to_value(recipient).unwrap();
+            assert_eq!(serialized, expected);
+            assert_eq!(
+                &json::from_value::<Recipient>(serialized).unwrap(),
+                recipient
+            );
+        };
+
+        round_trip(
+            &Recipient::chat_id(-1001234567890),
+            json::json!(-1001234567890_i64),
+        );
+        round_trip(
+            &Recipient::username("@my_channel"),
+            json::json!("@my_channel"),
+        );
+        round_trip(
+            &Recipient::from(("@my_chat", 5)),
+            json::json!({ "username": "@my_chat", "thread_id": 5 }),
+        );
+        round_trip(
+            &Recipient::builder()
+                .chat_id(-1001234567890)
+                .thread_id(114)
+                .reply_to(514)
+                .build(),
+            json::json!({ "chat_id": -1001234567890_i64, "thread_id": 114, "reply_to": 514 }),
+        );
+        round_trip(
+            &Recipient::linked_discussion(-1001234567890),
+            json::json!({ "chat_id": -1001234567890_i64, "linked_discussion": true }),
+        );
+        round_trip(
+            &Recipient::builder()
+                .chat_id(-1001234567890)
+                .reply_to_link("https://t.me/my_channel/456")
+                .unwrap()
+                .build(),
+            json::json!({
+                "chat_id": -1001234567890_i64,
+                "reply_to": { "message_id": 456, "username": "@my_channel" },
+            }),
+        );
+        round_trip(
+            &Recipient::builder()
+                .username("@my_chat")
+                .reply_to_with_quote(
+                    514,
+                    Quote::new("web-3").position(28).entities(vec![
+                        json::json!({ "type": "bold", "offset": 0, "length": 5 }),
+                    ]),
+                )
+                .build(),
+            json::json!({
+                "username": "@my_chat",
+                "reply_to": {
+                    "message_id": 514,
+                    "quote": {
+                        "text": "web-3",
+                        "position": 28,
+                        "entities": [{ "type": "bold", "offset": 0, "length": 5 }],
+                    },
+                },
+            }),
+        );
+
+        for invalid in [
+            json::json!(true),
+            json::json!(u64::MAX),
+            json::json!({ "thread_id": 5 }),
+            json::json!({ "chat_id": 1, "username": "@my_chat" }),
+            json::json!({ "chat_id": "1" }),
+            json::json!({ "chat_id": 1, "thread": 5 }),
+            json::json!({ "chat_id": 1, "reply_to": { "id": 514 } }),
+        ] {
+            assert!(
+                json::from_value::<Recipient>(invalid.clone()).is_err(),
+                "{invalid}"
+            );
+        }
     }
 }
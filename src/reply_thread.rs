@@ -0,0 +1,67 @@
+use std::{collections::VecDeque, sync::Mutex};
+
+// Remembers the first message sent for each value of a key-value, so that
+// subsequent logs with the same value reply to it.
+//
+// The number of remembered values is bounded, the least recently used one is
+// evicted first.
+pub(crate) struct ReplyThreads {
+    capacity: usize,
+    // The most recently used is at the back
+    roots: Mutex<VecDeque<(String, u64)>>,
+}
+
+impl ReplyThreads {
+    pub(crate) const DEFAULT_CAPACITY: usize = 1024;
+
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            roots: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    // Returns the message ID of the first message with the value, if remembered.
+    pub(crate) fn root(&self, value: &str) -> Option<u64> {
+        let mut roots = self.roots.lock().unwrap_or_else(|err| err.into_inner());
+        let index = roots.iter().position(|(v, _)| v == value)?;
+        let root = roots.remove(index).unwrap();
+        let message_id = root.1;
+        roots.push_back(root);
+        Some(message_id)
+    }
+
+    // Remembers the message as the first one with the value, unless another one
+    // has been remembered meanwhile.
+    pub(crate) fn insert(&self, value: String, message_id: u64) {
+        let mut roots = self.roots.lock().unwrap_or_else(|err| err.into_inner());
+        if roots.iter().any(|(v, _)| *v == value) {
+            return;
+        }
+        if roots.len() >= self.capacity {
+            roots.pop_front();
+        }
+        roots.push_back((value, message_id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lru() {
+        let threads = ReplyThreads::new(2);
+        threads.insert("a".into(), 1);
+        threads.insert("b".into(), 2);
+        // Not the first message
+        threads.insert("a".into(), 3);
+        assert_eq!(threads.root("a"), Some(1));
+
+        // Evicts "b", as "a" is used more recently
+        threads.insert("c".into(), 4);
+        assert_eq!(threads.root("b"), None);
+        assert_eq!(threads.root("a"), Some(1));
+        assert_eq!(threads.root("c"), Some(4));
+    }
+}
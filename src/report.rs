@@ -0,0 +1,87 @@
+/// Represents diagnostics of building a [`TelegramSink`], returned by
+/// [`TelegramSinkBuilder::build_with_report`].
+///
+/// It captures the effective configuration, e.g. to be logged at startup for
+/// verification. The bot token is never included.
+///
+/// [`TelegramSink`]: crate::TelegramSink
+/// [`TelegramSinkBuilder::build_with_report`]: crate::TelegramSinkBuilder::build_with_report
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BuildReport {
+    pub(crate) token_validated: bool,
+    pub(crate) endpoint_redacted: Option<String>,
+    pub(crate) default_pattern: Option<String>,
+    pub(crate) link_preview: bool,
+    pub(crate) http2_prior_knowledge: bool,
+    pub(crate) dry_run: bool,
+}
+
+impl BuildReport {
+    /// Returns `true` if the bot token was validated by a successful Bot API
+    /// call while building, i.e. resolving the [linked discussion group] or
+    /// creating the [forum topic].
+    ///
+    /// Building makes no network operations otherwise, see
+    /// [`TelegramSinkBuilder::build_and_test`] to validate the token
+    /// explicitly. This is the same as [`TelegramSink::token_validated`]
+    /// returning `Some(true)`.
+    ///
+    /// [linked discussion group]: crate::Recipient::linked_discussion
+    /// [forum topic]: crate::TelegramSinkBuilder::create_topic
+    /// [`TelegramSinkBuilder::build_and_test`]: crate::TelegramSinkBuilder::build_and_test
+    /// [`TelegramSink::token_validated`]: crate::TelegramSink::token_validated
+    #[must_use]
+    pub fn token_validated(&self) -> bool {
+        self.token_validated
+    }
+
+    /// Gets the `sendMessage` endpoint with the bot token redacted.
+    ///
+    /// See [`TelegramSink::endpoint_redacted`].
+    ///
+    /// [`TelegramSink::endpoint_redacted`]: crate::TelegramSink::endpoint_redacted
+    #[must_use]
+    pub fn endpoint_redacted(&self) -> Option<&str> {
+        self.endpoint_redacted.as_deref()
+    }
+
+    /// Gets the pattern the default formatter is equivalent to.
+    ///
+    /// See [`TelegramSink::default_pattern`].
+    ///
+    /// [`TelegramSink::default_pattern`]: crate::TelegramSink::default_pattern
+    #[must_use]
+    pub fn default_pattern(&self) -> Option<&str> {
+        self.default_pattern.as_deref()
+    }
+
+    /// Returns `true` if link previews are shown, either specified with
+    /// [`TelegramSinkBuilder::link_preview`] or chosen by the parse mode.
+    ///
+    /// [`TelegramSinkBuilder::link_preview`]: crate::TelegramSinkBuilder::link_preview
+    #[must_use]
+    pub fn link_preview(&self) -> bool {
+        self.link_preview
+    }
+
+    /// Returns `true` if the default HTTP client uses HTTP/2 with prior
+    /// knowledge.
+    ///
+    /// It's `false` for the official server, a custom HTTP client or a custom
+    /// transport, even if [`TelegramSinkBuilder::http2_prior_knowledge`] is
+    /// enabled.
+    ///
+    /// [`TelegramSinkBuilder::http2_prior_knowledge`]: crate::TelegramSinkBuilder::http2_prior_knowledge
+    #[must_use]
+    pub fn http2_prior_knowledge(&self) -> bool {
+        self.http2_prior_knowledge
+    }
+
+    /// Returns `true` if [dry-run mode] is enabled.
+    ///
+    /// [dry-run mode]: crate::TelegramSinkBuilder::dry_run
+    #[must_use]
+    pub fn dry_run(&self) -> bool {
+        self.dry_run
+    }
+}
@@ -1,20 +1,73 @@
-use reqwest::header::CONTENT_TYPE;
+use std::{
+    io::Write,
+    iter,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use arc_swap::ArcSwap;
 use serde_json::{self as json, json};
-use url::Url;
+use spdlog::{Level, LevelFilter};
+
+use crate::{
+    Button, Error, ParseMode, Recipient, Result, TopicIconColor, Transport,
+    reply_thread::ReplyThreads,
+};
+
+// Matches the upload limit of Telegram for bots, 50 MiB.
+pub(crate) const DEFAULT_MAX_REQUEST_BYTES: usize = 50 * 1024 * 1024;
 
-use crate::{Error, Recipient, Result};
+pub(crate) type BeforeSendFn = Box<dyn Fn(&mut json::Value) -> bool + Send + Sync>;
+
+pub(crate) type LatencyFn = Box<dyn Fn(Duration, &json::Value) + Send + Sync>;
+
+// A message to be sent for a log.
+pub(crate) struct Message {
+    pub(crate) text: String,
+    pub(crate) disable_notification: bool,
+    pub(crate) parse_mode: Option<ParseMode>,
+    // Overrides the thread ID of the recipient if present
+    pub(crate) thread_id: Option<u64>,
+    // Overrides the reply of the recipient if present
+    pub(crate) reply_to: Option<u64>,
+    // Rows of inline keyboard buttons under the message
+    pub(crate) buttons: Option<Vec<Vec<Button>>>,
+    // Selects the recipient by the recipient rules, `None` for the default one
+    pub(crate) level: Option<Level>,
+}
 
 pub(crate) struct Requester {
-    client: reqwest::blocking::Client,
-    endpoint: Url,
-    payload: json::Value,
+    transport: Box<dyn Transport>,
+    // Swappable, as the chat ID changes if it's migrated
+    payload: ArcSwap<json::Value>,
+    // The recipient rules with their own payloads, the first match wins
+    rules: Vec<(LevelFilter, ArcSwap<json::Value>)>,
+    // Requests are written here instead of being sent, if present
+    dry_run: Option<Mutex<Box<dyn Write + Send>>>,
+    follow_chat_migration: bool,
+    // Rejects recipients in the General topic of forums if enabled
+    require_topic: bool,
+    // The names of the fields `ok` and `description` in responses
+    ok_field: String,
+    description_field: String,
+    // Set in all `reply_parameters` if enabled
+    allow_sending_without_reply: bool,
+    // The first messages to reply to for values of the thread key, if enabled
+    reply_threads: Option<ReplyThreads>,
+    // Only checked in dry-run mode, the HTTP transport checks its bodies itself
+    max_request_bytes: usize,
+    // Called with the payload of every log, the log is dropped if it returns `false`
+    before_send: Option<BeforeSendFn>,
+    // Called with the round-trip time and the chat ID of every `sendMessage` call
+    on_latency: Option<LatencyFn>,
 }
 
 impl Requester {
-    pub(crate) fn new(server_url: Url, bot_token: &str, recipient: Recipient) -> Result<Self> {
-        let mut payload = json!({
-            "chat_id": recipient.target.into_json(),
-            "message_thread_id": recipient.thread_id,
+    // The recipient is not bound until `bind` is called.
+    pub(crate) fn new(transport: Box<dyn Transport>) -> Self {
+        let payload = json!({
+            "chat_id": null,
+            "message_thread_id": null,
             "text": null,
             "link_preview_options": {
                 "is_disabled": true,
@@ -22,62 +75,420 @@ impl Requester {
             "disable_notification": null,
         });
 
+        Self {
+            transport,
+            payload: ArcSwap::from_pointee(payload),
+            rules: vec![],
+            dry_run: None,
+            follow_chat_migration: false,
+            require_topic: false,
+            ok_field: "ok".into(),
+            description_field: "description".into(),
+            allow_sending_without_reply: false,
+            reply_threads: None,
+            max_request_bytes: DEFAULT_MAX_REQUEST_BYTES,
+            before_send: None,
+            on_latency: None,
+        }
+    }
+
+    // Sends all subsequent logs to the recipient, resolving its linked discussion
+    // group and creating the forum topic if needed.
+    //
+    // The payload is swapped at once, so that concurrent sends never see a
+    // partially bound recipient.
+    pub(crate) fn bind(
+        &self,
+        recipient: Recipient,
+        create_topic: Option<&(String, Option<TopicIconColor>)>,
+    ) -> Result<()> {
+        let payload = self.prepare(recipient, create_topic)?;
+        self.payload.store(Arc::new(payload));
+        Ok(())
+    }
+
+    // Sends logs matching the level filter to the recipient instead, unless an
+    // earlier rule matches.
+    //
+    // The payload is based on the default one, so rules must be added after all
+    // other options are set.
+    pub(crate) fn add_rule(
+        &mut self,
+        level_filter: LevelFilter,
+        recipient: Recipient,
+        create_topic: Option<&(String, Option<TopicIconColor>)>,
+    ) -> Result<()> {
+        let payload = self.prepare(recipient, create_topic)?;
+        self.rules
+            .push((level_filter, ArcSwap::from_pointee(payload)));
+        Ok(())
+    }
+
+    fn prepare(
+        &self,
+        recipient: Recipient,
+        create_topic: Option<&(String, Option<TopicIconColor>)>,
+    ) -> Result<json::Value> {
+        let mut payload = json::Value::clone(&self.payload.load());
+        payload["chat_id"] = recipient.target.into_json();
+        payload["message_thread_id"] = recipient.thread_id.into();
+
         // Telegram server requires the field `reply_parameters` must be an object or
         // not present, but a JSON `null` will be rejected.
-        if let Some((message_id, target)) = recipient.reply_to {
-            let payload = payload.as_object_mut().unwrap();
+        let payload_map = payload.as_object_mut().unwrap();
+        match recipient.reply_to {
+            Some(reply) => {
+                let mut reply_parameters = json!({
+                    "message_id": reply.message_id,
+                    "chat_id": reply.chat.map(|t| t.into_json()),
+                });
+                if let Some(quote) = &reply.quote {
+                    quote.write_to(reply_parameters.as_object_mut().unwrap());
+                }
+                self.write_allow_sending_without_reply(&mut reply_parameters);
+                payload_map.insert("reply_parameters".into(), reply_parameters);
+            }
+            None => {
+                payload_map.remove("reply_parameters");
+            }
+        }
+
+        if recipient.linked_discussion
+            && let Some(linked_chat_id) = self.linked_discussion(&payload["chat_id"])?
+        {
+            payload["chat_id"] = linked_chat_id.into();
+        }
+        if let Some((name, icon_color)) = create_topic
+            && let Some(thread_id) =
+                self.create_forum_topic(&payload["chat_id"], name, *icon_color)?
+        {
+            payload["message_thread_id"] = thread_id.into();
+        }
+        if self.require_topic
+            && payload["message_thread_id"].is_null()
+            && self.is_forum(&payload["chat_id"])?
+        {
+            return Err(Error::TopicRequired);
+        }
+        Ok(payload)
+    }
+
+    // Returns the chat ID of the recipient for the level.
+    pub(crate) fn chat_id_for(&self, level: Option<Level>) -> json::Value {
+        self.payload_for(level).load()["chat_id"].clone()
+    }
+
+    fn payload_for(&self, level: Option<Level>) -> &ArcSwap<json::Value> {
+        level
+            .and_then(|level| {
+                self.rules
+                    .iter()
+                    .find(|(level_filter, _)| level_filter.test(level))
+            })
+            .map_or(&self.payload, |(_, payload)| payload)
+    }
+
+    // Uses the template as the base of the payload, fields already set by the
+    // crate take precedence.
+    pub(crate) fn set_payload_template(&mut self, mut template: json::Map<String, json::Value>) {
+        let payload = self.payload.load();
+        for (key, value) in payload.as_object().unwrap() {
+            template.insert(key.clone(), value.clone());
+        }
+        self.payload.store(Arc::new(json::Value::Object(template)));
+    }
+
+    pub(crate) fn set_require_topic(&mut self, require: bool) {
+        self.require_topic = require;
+    }
+
+    pub(crate) fn set_follow_chat_migration(&mut self, follow: bool) {
+        self.follow_chat_migration = follow;
+    }
+
+    pub(crate) fn set_response_fields(&mut self, ok: String, description: String) {
+        self.ok_field = ok;
+        self.description_field = description;
+    }
+
+    pub(crate) fn set_max_request_bytes(&mut self, max_bytes: usize) {
+        self.max_request_bytes = max_bytes;
+    }
+
+    pub(crate) fn set_before_send(&mut self, before_send: BeforeSendFn) {
+        self.before_send = Some(before_send);
+    }
+
+    pub(crate) fn set_on_latency(&mut self, on_latency: LatencyFn) {
+        self.on_latency = Some(on_latency);
+    }
+
+    pub(crate) fn set_reply_threads(&mut self, capacity: usize) {
+        self.reply_threads = Some(ReplyThreads::new(capacity));
+    }
+
+    pub(crate) fn reply_threads(&self) -> Option<&ReplyThreads> {
+        self.reply_threads.as_ref()
+    }
+
+    pub(crate) fn set_allow_sending_without_reply(&mut self, allow: bool) {
+        self.allow_sending_without_reply = allow;
+    }
+
+    fn write_allow_sending_without_reply(&self, reply_parameters: &mut json::Value) {
+        if self.allow_sending_without_reply {
+            reply_parameters["allow_sending_without_reply"] = true.into();
+        }
+    }
+
+    // Sends the deprecated `disable_web_page_preview` field instead if `legacy`.
+    pub(crate) fn set_link_preview(&mut self, enabled: bool, legacy: bool) {
+        let mut payload = json::Value::clone(&self.payload.load());
+        if legacy {
+            let payload_map = payload.as_object_mut().unwrap();
+            payload_map.remove("link_preview_options");
+            payload_map.insert("disable_web_page_preview".into(), (!enabled).into());
+        } else {
+            payload["link_preview_options"]["is_disabled"] = (!enabled).into();
+        }
+        self.payload.store(Arc::new(payload));
+    }
+
+    pub(crate) fn set_allow_paid_broadcast(&mut self) {
+        let mut payload = json::Value::clone(&self.payload.load());
+        payload["allow_paid_broadcast"] = true.into();
+        self.payload.store(Arc::new(payload));
+    }
+
+    pub(crate) fn set_dry_run(&mut self, writer: Box<dyn Write + Send>) {
+        self.dry_run = Some(Mutex::new(writer));
+    }
+
+    // Returns the sent message, or `None` if it's dropped by the `before_send`
+    // hook.
+    pub(crate) fn send_log(&self, message: &Message) -> Result<Option<json::Value>> {
+        match self.send_log_once(message) {
+            Err(Error::ChatMigrated { migrate_to_chat_id }) if self.follow_chat_migration => {
+                // Every recipient in the group is migrated, not only the one for the
+                // level, so that they don't have to fail once each.
+                let old_chat_id = self.chat_id_for(message.level);
+                for payload_swap in
+                    iter::once(&self.payload).chain(self.rules.iter().map(|(_, payload)| payload))
+                {
+                    if payload_swap.load()["chat_id"] == old_chat_id {
+                        let mut payload = json::Value::clone(&payload_swap.load());
+                        payload["chat_id"] = migrate_to_chat_id.into();
+                        payload_swap.store(Arc::new(payload));
+                    }
+                }
+                self.send_log_once(message)
+            }
+            result => result,
+        }
+    }
+
+    fn send_log_once(&self, message: &Message) -> Result<Option<json::Value>> {
+        let mut payload = self
+            .payload_for(message.level)
+            .load()
+            .as_object()
+            .unwrap()
+            .clone();
+        payload["text"] = json::Value::String(message.text.clone());
+        payload["disable_notification"] = json::Value::Bool(message.disable_notification);
+        if let Some(parse_mode) = message.parse_mode {
+            payload.insert("parse_mode".into(), parse_mode.as_str().into());
+        }
+        if let Some(thread_id) = message.thread_id {
+            payload["message_thread_id"] = thread_id.into();
+        }
+        if let Some(message_id) = message.reply_to {
+            let mut reply_parameters = json!({ "message_id": message_id });
+            self.write_allow_sending_without_reply(&mut reply_parameters);
+            payload.insert("reply_parameters".into(), reply_parameters);
+        }
+
+        if let Some(buttons) = &message.buttons {
+            let inline_keyboard = buttons
+                .iter()
+                .map(|row| row.iter().map(Button::to_json).collect())
+                .collect::<Vec<json::Value>>();
             payload.insert(
-                "reply_parameters".into(),
-                json!({
-                    "message_id": message_id,
-                    "chat_id": target.map(|t| t.into_json()),
-                }),
+                "reply_markup".into(),
+                json!({ "inline_keyboard": inline_keyboard }),
             );
         }
 
-        Ok(Self {
-            client: reqwest::blocking::Client::new(),
-            endpoint: server_url
-                .join(&format!("/bot{}/sendMessage", bot_token))
-                .map_err(Error::ParseUrl)?,
-            payload,
-        })
-    }
-
-    pub(crate) fn send_log(&self, text: String, disable_notification: bool) -> Result<()> {
-        let mut payload = self.payload.as_object().unwrap().clone();
-        payload["text"] = json::Value::String(text);
-        payload["disable_notification"] = json::Value::Bool(disable_notification);
-        let payload = json::Value::Object(payload);
-
-        let response = self
-            .client
-            .post(self.endpoint.as_str())
-            .header(CONTENT_TYPE, "application/json")
-            .body(payload.to_string())
-            .send()
-            .map_err(|err| Error::SendRequest(err.into()))?;
-
-        let status_unsuccess = !response.status().is_success();
-        let (ok, description) = response
-            .text()
-            .ok()
-            .and_then(|resp| json::from_str::<json::Value>(&resp).ok())
-            .and_then(|resp| {
-                resp.as_object().map(|resp| {
-                    (
-                        resp.get("ok").and_then(|j| j.as_bool()).unwrap_or(false),
-                        resp.get("description")
-                            .and_then(|j| j.as_str().map(str::to_string)),
-                    )
-                })
+        let mut payload = json::Value::Object(payload);
+        if let Some(before_send) = &self.before_send
+            && !before_send(&mut payload)
+        {
+            return Ok(None);
+        }
+        self.call("sendMessage", &payload).map(Some)
+    }
+
+    // Calls a cheap method, so that the transport opens and pools a connection.
+    pub(crate) fn get_me(&self) -> Result<()> {
+        self.call("getMe", &json!({})).map(|_| ())
+    }
+
+    pub(crate) fn delete_message(&self, chat_id: &json::Value, message_id: u64) -> Result<()> {
+        self.call(
+            "deleteMessage",
+            &json!({
+                "chat_id": chat_id,
+                "message_id": message_id,
+            }),
+        )
+        .map(|_| ())
+    }
+
+    pub(crate) fn forward_message(
+        &self,
+        from_chat_id: i64,
+        message_id: u64,
+        disable_notification: bool,
+        level: Level,
+    ) -> Result<json::Value> {
+        let payload = self.payload_for(Some(level)).load();
+        self.call(
+            "forwardMessage",
+            &json!({
+                "chat_id": payload["chat_id"],
+                "message_thread_id": payload["message_thread_id"],
+                "from_chat_id": from_chat_id,
+                "message_id": message_id,
+                "disable_notification": disable_notification,
+            }),
+        )
+    }
+
+    // Looks up the discussion group linked to the channel, returns `None` in
+    // dry-run mode.
+    fn linked_discussion(&self, channel_id: &json::Value) -> Result<Option<i64>> {
+        let result = self.call(
+            "getChat",
+            &json!({
+                "chat_id": channel_id,
+            }),
+        )?;
+        if self.dry_run.is_some() {
+            // There is no real chat, keep the channel.
+            return Ok(None);
+        }
+        result
+            .get("linked_chat_id")
+            .and_then(|j| j.as_i64())
+            .map(Some)
+            .ok_or(Error::NoLinkedDiscussion)
+    }
+
+    // Looks up whether the chat is a forum, returns `false` in dry-run mode.
+    fn is_forum(&self, chat_id: &json::Value) -> Result<bool> {
+        let result = self.call(
+            "getChat",
+            &json!({
+                "chat_id": chat_id,
+            }),
+        )?;
+        Ok(result
+            .get("is_forum")
+            .and_then(|j| j.as_bool())
+            .unwrap_or(false))
+    }
+
+    // Creates a forum topic in the chat, returns its thread ID, or `None` in
+    // dry-run mode.
+    pub(crate) fn create_forum_topic(
+        &self,
+        chat_id: &json::Value,
+        name: &str,
+        icon_color: Option<TopicIconColor>,
+    ) -> Result<Option<u64>> {
+        let result = self.call(
+            "createForumTopic",
+            &json!({
+                "chat_id": chat_id,
+                "name": name,
+                "icon_color": icon_color.map(|c| c as u32),
+            }),
+        )?;
+        if self.dry_run.is_some() {
+            // There is no real topic, keep the thread ID of the recipient.
+            return Ok(None);
+        }
+        result
+            .get("message_thread_id")
+            .and_then(|j| j.as_u64())
+            .map(Some)
+            .ok_or_else(|| Error::TelegramApi {
+                description: Some("missing `message_thread_id` in response".into()),
+                parameters: None,
             })
-            .unwrap_or((false, None));
+    }
 
-        if status_unsuccess || !ok {
-            Err(Error::TelegramApi(description))
+    // Calls a Bot API method, returns the `result` field of the response.
+    fn call(&self, method: &str, payload: &json::Value) -> Result<json::Value> {
+        if let Some(writer) = &self.dry_run {
+            // No transport serializes the body, so its size is checked here.
+            let body = payload.to_string();
+            if body.len() > self.max_request_bytes {
+                return Err(Error::RequestTooLarge {
+                    size: body.len(),
+                    limit: self.max_request_bytes,
+                });
+            }
+            // The endpoint is not written, as it contains the bot token.
+            let mut writer = writer.lock().unwrap_or_else(|err| err.into_inner());
+            writeln!(writer, "[spdlog-telegram dry-run] {method} {body}")
+                .and_then(|_| writer.flush())
+                .map_err(Error::DryRun)?;
+            return Ok(json::Value::Null);
+        }
+
+        // Only the transport is timed, waits before the request are excluded.
+        let started = Instant::now();
+        let response = self.transport.call(method, payload);
+        if let Some(on_latency) = &self.on_latency
+            && method == "sendMessage"
+        {
+            on_latency(started.elapsed(), &payload["chat_id"]);
+        }
+        let mut response = response?;
+        let Some((ok, resp)) = response
+            .as_object_mut()
+            .and_then(|resp| Some((resp.get(&self.ok_field)?.as_bool()?, resp)))
+        else {
+            return Err(Error::unexpected_response(None, &response.to_string()));
+        };
+        let error_code = resp.get("error_code").and_then(|j| j.as_u64());
+        let description = resp
+            .get(&self.description_field)
+            .and_then(|j| j.as_str().map(str::to_string));
+        let parameters = resp.remove("parameters");
+        let result = resp.remove("result").unwrap_or_default();
+
+        let migrate_to_chat_id = parameters
+            .as_ref()
+            .and_then(|j| j.get("migrate_to_chat_id"))
+            .and_then(|j| j.as_i64());
+        if let Some(migrate_to_chat_id) = migrate_to_chat_id {
+            Err(Error::ChatMigrated { migrate_to_chat_id })
+        } else if !ok
+            && error_code == Some(403)
+            && description
+                .as_deref()
+                .is_some_and(|d| d.contains("bot was blocked by the user"))
+        {
+            Err(Error::BotBlocked)
+        } else if !ok {
+            Err(Error::TelegramApi {
+                description,
+                parameters,
+            })
         } else {
-            Ok(())
+            Ok(result)
         }
     }
 }
@@ -0,0 +1,135 @@
+use std::{process, time::Duration};
+
+/// Represents which process metadata to append to every message as a footer.
+///
+/// The footer is rendered compactly, e.g. `[host=web-3 pid=4821]`.
+///
+/// ## Examples
+///
+/// ```
+/// use spdlog_telegram::SourceTag;
+///
+/// let tag = SourceTag {
+///     hostname: true,
+///     pid: true,
+///     custom: Some("region=eu".into()),
+/// };
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SourceTag {
+    /// Whether to include the hostname of the machine, as `host=<hostname>`.
+    pub hostname: bool,
+    /// Whether to include the ID of the current process, as `pid=<pid>`.
+    pub pid: bool,
+    /// A custom string to include as-is.
+    pub custom: Option<String>,
+}
+
+impl SourceTag {
+    // Hostname and PID are stable for the lifetime of the process, so the fields
+    // are rendered once when building the sink.
+    pub(crate) fn fields(&self) -> Vec<String> {
+        let mut fields = vec![];
+        if self.hostname
+            && let Some(hostname) = hostname()
+        {
+            fields.push(format!("host={hostname}"));
+        }
+        if self.pid {
+            fields.push(format!("pid={}", process::id()));
+        }
+        if let Some(custom) = &self.custom {
+            fields.push(custom.clone());
+        }
+        fields
+    }
+
+    #[cfg(test)]
+    fn render(&self) -> Option<String> {
+        render_fields(self.fields().iter().map(String::as_str))
+    }
+}
+
+// Renders footer fields compactly, e.g. `[host=web-3 pid=4821]`.
+pub(crate) fn render_fields<'a>(fields: impl IntoIterator<Item = &'a str>) -> Option<String> {
+    let fields = fields.into_iter().collect::<Vec<_>>();
+    if fields.is_empty() {
+        None
+    } else {
+        Some(format!("[{}]", fields.join(" ")))
+    }
+}
+
+// Formats an uptime compactly with its 2 most significant units, e.g. `2h13m`.
+pub(crate) fn format_uptime(uptime: Duration) -> String {
+    let secs = uptime.as_secs();
+    let (days, hours, mins, secs) = (secs / 86400, secs / 3600 % 24, secs / 60 % 60, secs % 60);
+    if days != 0 {
+        format!("{days}d{hours}h")
+    } else if hours != 0 {
+        format!("{hours}h{mins}m")
+    } else if mins != 0 {
+        format!("{mins}m{secs}s")
+    } else {
+        format!("{secs}s")
+    }
+}
+
+#[cfg(unix)]
+fn hostname() -> Option<String> {
+    let mut buf = [0u8; 256];
+    // SAFETY: The buffer is valid for writes of `buf.len()` bytes.
+    let ret = unsafe { libc::gethostname(buf.as_mut_ptr().cast(), buf.len()) };
+    if ret != 0 {
+        return None;
+    }
+    let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+    Some(String::from_utf8_lossy(&buf[..len]).into_owned())
+}
+
+#[cfg(not(unix))]
+fn hostname() -> Option<String> {
+    std::env::var("COMPUTERNAME").ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render() {
+        assert_eq!(SourceTag::default().render(), None);
+        assert_eq!(
+            SourceTag {
+                hostname: false,
+                pid: true,
+                custom: Some("region=eu".into()),
+            }
+            .render(),
+            Some(format!("[pid={} region=eu]", process::id()))
+        );
+        assert!(
+            SourceTag {
+                hostname: true,
+                ..Default::default()
+            }
+            .render()
+            .unwrap()
+            .starts_with("[host=")
+        );
+    }
+
+    #[test]
+    fn uptime() {
+        for (secs, expected) in [
+            (0, "0s"),
+            (42, "42s"),
+            (60, "1m0s"),
+            (312, "5m12s"),
+            (2 * 3600 + 13 * 60 + 59, "2h13m"),
+            (3 * 86400 + 4 * 3600 + 5, "3d4h"),
+        ] {
+            assert_eq!(format_uptime(Duration::from_secs(secs)), expected);
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use spdlog::Level;
+
+const LEVEL_COUNT: usize = Level::count();
+
+/// Represents a snapshot of the send counters of a [`TelegramSink`].
+///
+/// [`TelegramSink`]: crate::TelegramSink
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SendStats {
+    delivered: [u64; LEVEL_COUNT],
+    failures: u64,
+    retries: u64,
+}
+
+impl SendStats {
+    /// Gets the number of logs with the given level delivered successfully.
+    #[must_use]
+    pub fn delivered(&self, level: Level) -> u64 {
+        self.delivered[level as usize]
+    }
+
+    /// Gets the total number of logs delivered successfully.
+    #[must_use]
+    pub fn total_delivered(&self) -> u64 {
+        self.delivered.iter().sum()
+    }
+
+    /// Gets the number of logs that failed to be delivered.
+    #[must_use]
+    pub fn failures(&self) -> u64 {
+        self.failures
+    }
+
+    /// Gets the number of retried sends.
+    ///
+    /// See [`TelegramSinkBuilder::retry_predicate`].
+    ///
+    /// [`TelegramSinkBuilder::retry_predicate`]: crate::TelegramSinkBuilder::retry_predicate
+    #[must_use]
+    pub fn retries(&self) -> u64 {
+        self.retries
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct SendCounters {
+    delivered: [AtomicU64; LEVEL_COUNT],
+    failures: AtomicU64,
+    retries: AtomicU64,
+}
+
+impl SendCounters {
+    pub(crate) fn record(&self, level: Level, succeeded: bool) {
+        let counter = if succeeded {
+            &self.delivered[level as usize]
+        } else {
+            &self.failures
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_retry(&self) {
+        self.retries.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn snapshot(&self) -> SendStats {
+        SendStats {
+            delivered: self
+                .delivered
+                .each_ref()
+                .map(|counter| counter.load(Ordering::Relaxed)),
+            failures: self.failures.load(Ordering::Relaxed),
+            retries: self.retries.load(Ordering::Relaxed),
+        }
+    }
+
+    pub(crate) fn reset(&self) {
+        self.delivered
+            .iter()
+            .chain([&self.failures, &self.retries])
+            .for_each(|counter| counter.store(0, Ordering::Relaxed));
+    }
+}
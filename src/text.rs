@@ -0,0 +1,137 @@
+// Telegram measures message length and entity offsets in UTF-16 code units, so
+// all length and offset calculations in this crate must go through the helpers
+// here rather than `str::len` or `str::chars().count()`, otherwise they will be
+// misaligned around characters outside the BMP (e.g. most emoji).
+
+/// The maximum length of a message text accepted by Telegram, in UTF-16 code
+/// units.
+pub(crate) const MAX_TEXT_LEN: usize = 4096;
+
+pub(crate) const ELLIPSIS: &str = "…";
+
+pub(crate) fn utf16_len(text: &str) -> usize {
+    text.chars().map(char::len_utf16).sum()
+}
+
+// Truncates `text` so that its length including the ellipsis does not exceed
+// `max_len` UTF-16 code units. Does nothing if it already fits.
+//
+// The ellipsis is omitted if it doesn't fit in `max_len` by itself.
+pub(crate) fn truncate_utf16(text: &mut String, max_len: usize, ellipsis: &str) {
+    truncate_utf16_with(text, max_len, ellipsis, |_, end| end);
+}
+
+// Same as `truncate_utf16`, but `boundary` is called with the text and the end
+// of the kept part, and returns the end actually cut at, e.g. moved back so
+// that an escape sequence is not split.
+pub(crate) fn truncate_utf16_with(
+    text: &mut String,
+    max_len: usize,
+    ellipsis: &str,
+    boundary: impl FnOnce(&str, usize) -> usize,
+) {
+    if utf16_len(text) <= max_len {
+        return;
+    }
+    let ellipsis = if utf16_len(ellipsis) <= max_len {
+        ellipsis
+    } else {
+        ""
+    };
+    let budget = max_len - utf16_len(ellipsis);
+
+    let mut len = 0;
+    let end = text
+        .char_indices()
+        .find(|(_, ch)| {
+            len += ch.len_utf16();
+            len > budget
+        })
+        .map_or(text.len(), |(idx, _)| idx);
+    let end = boundary(text, end);
+    text.truncate(end);
+    text.push_str(ellipsis);
+}
+
+// Trims whitespace around `text`. If `keep_indent`, the indentation of the
+// first line is kept, e.g. for code blocks, only blank lines before it are
+// removed.
+pub(crate) fn trim(text: &mut String, keep_indent: bool) {
+    text.truncate(text.trim_end().len());
+    let leading = &text[..text.len() - text.trim_start().len()];
+    let start = if keep_indent {
+        leading.rfind('\n').map_or(0, |idx| idx + 1)
+    } else {
+        leading.len()
+    };
+    text.drain(..start);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncate() {
+        let mut text = "Hello Telegram!".to_string();
+        truncate_utf16(&mut text, 15, ELLIPSIS);
+        assert_eq!(text, "Hello Telegram!");
+        truncate_utf16(&mut text, 6, ELLIPSIS);
+        assert_eq!(text, "Hello…");
+    }
+
+    #[test]
+    fn custom_ellipsis() {
+        let mut text = "Hello Telegram!".to_string();
+        truncate_utf16(&mut text, 14, " [truncated]");
+        assert_eq!(text, "He [truncated]");
+
+        // Omitted if it doesn't fit by itself
+        let mut text = "Hello Telegram!".to_string();
+        truncate_utf16(&mut text, 5, " [truncated]");
+        assert_eq!(text, "Hello");
+
+        let mut text = "Hello Telegram!".to_string();
+        truncate_utf16(&mut text, 9, "");
+        assert_eq!(text, "Hello Tel");
+    }
+
+    #[test]
+    fn trim() {
+        let trimmed = |text: &str, keep_indent| {
+            let mut text = text.to_string();
+            super::trim(&mut text, keep_indent);
+            text
+        };
+        assert_eq!(trimmed("  Hello Telegram! \n\n", false), "Hello Telegram!");
+        assert_eq!(trimmed("a \n  b", false), "a \n  b");
+        assert_eq!(
+            trimmed("\n \n  at main\n  at foo\n", true),
+            "  at main\n  at foo"
+        );
+        assert_eq!(trimmed("  at main", true), "  at main");
+        assert_eq!(trimmed(" \t\n", false), "");
+    }
+
+    #[test]
+    fn surrogate_pairs() {
+        // U+1F600 is encoded as a surrogate pair in UTF-16, while it's 1 `char` and
+        // 4 bytes in UTF-8.
+        assert_eq!(utf16_len("😀"), 2);
+        assert_eq!(utf16_len("a😀b"), 4);
+        assert_eq!(utf16_len("🏳️‍🌈"), 6);
+
+        let mut text = "😀😀😀".to_string();
+        truncate_utf16(&mut text, 6, ELLIPSIS);
+        assert_eq!(text, "😀😀😀");
+
+        // Never splits a surrogate pair, even if there would be 1 code unit left.
+        let mut text = "a😀😀".to_string();
+        truncate_utf16(&mut text, 3, ELLIPSIS);
+        assert_eq!(text, "a…");
+
+        let mut text = "😀😀😀".to_string();
+        truncate_utf16(&mut text, 5, ELLIPSIS);
+        assert_eq!(text, "😀😀…");
+    }
+}
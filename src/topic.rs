@@ -0,0 +1,19 @@
+/// Represents the color of a forum topic icon.
+///
+/// Telegram only accepts these predefined colors when creating a topic.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum TopicIconColor {
+    /// `#6FB9F0`
+    Blue = 0x6FB9F0,
+    /// `#FFD67E`
+    Yellow = 0xFFD67E,
+    /// `#CB86DB`
+    Violet = 0xCB86DB,
+    /// `#8EEE98`
+    Green = 0x8EEE98,
+    /// `#FF93B2`
+    Rose = 0xFF93B2,
+    /// `#FB6F5F`
+    Red = 0xFB6F5F,
+}
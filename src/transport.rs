@@ -0,0 +1,501 @@
+use std::{net::IpAddr, sync::Mutex};
+
+use bytes::{BufMut, Bytes, BytesMut};
+use reqwest::header::CONTENT_TYPE;
+use serde_json as json;
+use url::{Position, Url};
+
+use crate::{Error, ReqwestDesensitizedError, Result, request::DEFAULT_MAX_REQUEST_BYTES};
+
+/// Represents a transport delivering Bot API requests.
+///
+/// By default, requests are sent to the Telegram Bot API server over HTTP.
+/// Implement this trait to deliver them in another way, e.g. writing to a
+/// message queue that another service relays to Telegram, or capturing them
+/// in tests. See [`TelegramSinkBuilder::transport`].
+///
+/// It's also the integration point for shared HTTP stacks, e.g. an async
+/// client with middlewares such as `reqwest-middleware`, which has no blocking
+/// API. Block on the request in your runtime from [`Transport::call`]. Avoid
+/// retry middlewares if [`TelegramSinkBuilder::retry_predicate`] is also
+/// configured, or requests will be retried twice.
+///
+/// ## Examples
+///
+/// ```
+/// use serde_json::{Value, json};
+/// use spdlog_telegram::{Result, Transport};
+///
+/// struct Stdout;
+///
+/// impl Transport for Stdout {
+///     fn call(&self, method: &str, payload: &Value) -> Result<Value> {
+///         println!("{method}: {payload}");
+///         Ok(json!({ "ok": true, "result": {} }))
+///     }
+/// }
+/// ```
+///
+/// [`TelegramSinkBuilder::transport`]: crate::TelegramSinkBuilder::transport
+/// [`TelegramSinkBuilder::retry_predicate`]: crate::TelegramSinkBuilder::retry_predicate
+pub trait Transport: Send + Sync {
+    /// Calls a Bot API method with the JSON payload, returns the JSON
+    /// response.
+    ///
+    /// The response is expected to be a [Bot API response object], i.e. with
+    /// field `ok` and either `result` or `description`. Error responses should
+    /// be returned as `Ok` too, they are interpreted by the sink. Return `Err`
+    /// only if the request could not be delivered. Responses without the field
+    /// `ok` are reported as [`Error::UnexpectedResponse`]. The field names can
+    /// be changed with [`TelegramSinkBuilder::ok_field`] and
+    /// [`TelegramSinkBuilder::description_field`].
+    ///
+    /// [Bot API response object]: https://core.telegram.org/bots/api#making-requests
+    /// [`TelegramSinkBuilder::ok_field`]: crate::TelegramSinkBuilder::ok_field
+    /// [`TelegramSinkBuilder::description_field`]: crate::TelegramSinkBuilder::description_field
+    fn call(&self, method: &str, payload: &json::Value) -> Result<json::Value>;
+}
+
+// Options for building the HTTP client, ignored if a custom client is given.
+#[derive(Default)]
+pub(crate) struct ClientOptions {
+    pub(crate) http2_prior_knowledge: bool,
+    // `None` for reqwest's defaults
+    pub(crate) tcp_nodelay: Option<bool>,
+    pub(crate) local_address: Option<IpAddr>,
+}
+
+impl ClientOptions {
+    fn build(self) -> reqwest::Result<reqwest::blocking::Client> {
+        let mut builder = reqwest::blocking::Client::builder();
+        if self.http2_prior_knowledge {
+            builder = builder.http2_prior_knowledge();
+        }
+        if let Some(enabled) = self.tcp_nodelay {
+            builder = builder.tcp_nodelay(enabled);
+        }
+        builder.local_address(self.local_address).build()
+    }
+}
+
+// Request bodies up to this size are serialized into the reused buffer of the
+// transport. A message text is at most 4096 UTF-16 code units, so this fits the
+// body of any log, unless there's a huge payload template.
+const BODY_CAPACITY: usize = 16 * 1024;
+
+// The default transport, sending requests to the Bot API server over HTTP.
+pub(crate) struct HttpTransport {
+    client: reqwest::blocking::Client,
+    api_url: Url,
+    content_type: Option<String>,
+    // The name of the field `ok` in responses
+    ok_field: String,
+    // Removes URLs, which contain the bot token, from errors if enabled
+    desensitize_errors: bool,
+    max_request_bytes: usize,
+    // Request bodies are serialized into it and split off, its space is reclaimed
+    // once they have been sent, so that no allocation is needed per request.
+    body: Mutex<BytesMut>,
+}
+
+impl HttpTransport {
+    pub(crate) fn new(
+        server_url: Url,
+        bot_token: &str,
+        client: Option<reqwest::blocking::Client>,
+        content_type: Option<String>,
+        options: ClientOptions,
+    ) -> Result<Self> {
+        let client = match client {
+            Some(client) => client,
+            None => options
+                .build()
+                .map_err(|err| Error::SendRequest(err.into()))?,
+        };
+        Ok(Self {
+            client,
+            api_url: server_url
+                .join(&format!("/bot{}/", bot_token))
+                .map_err(Error::ParseUrl)?,
+            content_type,
+            ok_field: "ok".into(),
+            desensitize_errors: true,
+            max_request_bytes: DEFAULT_MAX_REQUEST_BYTES,
+            body: Mutex::new(BytesMut::new()),
+        })
+    }
+
+    pub(crate) fn set_ok_field(&mut self, field: String) {
+        self.ok_field = field;
+    }
+
+    pub(crate) fn set_desensitize_errors(&mut self, desensitize: bool) {
+        self.desensitize_errors = desensitize;
+    }
+
+    pub(crate) fn set_max_request_bytes(&mut self, max_bytes: usize) {
+        self.max_request_bytes = max_bytes;
+    }
+
+    fn request_error(&self, err: reqwest::Error) -> Error {
+        Error::SendRequest(if self.desensitize_errors {
+            err.into()
+        } else {
+            ReqwestDesensitizedError::with_url(err)
+        })
+    }
+
+    // Returns the endpoint of the method with the bot token masked, for debugging.
+    pub(crate) fn endpoint_redacted(&self, method: &str) -> String {
+        format!(
+            "{}/bot<token>/{method}",
+            &self.api_url[..Position::BeforePath]
+        )
+    }
+
+    // Serializes the request body, failing if it exceeds the size limit.
+    fn serialize(&self, payload: &json::Value) -> Result<Bytes> {
+        let mut buf = self.body.lock().unwrap_or_else(|err| err.into_inner());
+        // Reclaims the space of the bodies already sent, a new buffer is only
+        // allocated if they are still in flight, e.g. for concurrent requests.
+        buf.reserve(BODY_CAPACITY);
+        // Serializing a `Value` into a `BytesMut` never fails.
+        json::to_writer((&mut *buf).writer(), payload).unwrap();
+        let body = buf.split().freeze();
+        if body.len() > BODY_CAPACITY {
+            // The buffer has grown for an outlier, don't keep that much space.
+            *buf = BytesMut::new();
+        }
+        if body.len() > self.max_request_bytes {
+            return Err(Error::RequestTooLarge {
+                size: body.len(),
+                limit: self.max_request_bytes,
+            });
+        }
+        Ok(body)
+    }
+}
+
+impl Transport for HttpTransport {
+    fn call(&self, method: &str, payload: &json::Value) -> Result<json::Value> {
+        let body = self.serialize(payload)?;
+        let endpoint = self.api_url.join(method).map_err(Error::ParseUrl)?;
+        let mut request = self.client.post(endpoint);
+        if let Some(content_type) = &self.content_type {
+            request = request.header(CONTENT_TYPE, content_type);
+        }
+        let response = request
+            .body(body)
+            .send()
+            .map_err(|err| self.request_error(err))?;
+
+        let status = response.status();
+        let body = response.bytes().map_err(|err| self.request_error(err))?;
+        let mut response = parse_response(status.as_u16(), &body, &self.ok_field)?;
+        // Never treat an unsuccessful HTTP status as a success, whatever the body is.
+        if !status.is_success()
+            && let Some(response) = response.as_object_mut()
+        {
+            response.insert(self.ok_field.clone(), false.into());
+        }
+        Ok(response)
+    }
+}
+
+// Parses the response body, tolerating servers that don't respond in UTF-8.
+//
+// Invalid UTF-8 sequences are replaced rather than failing the whole response.
+// A body that is not a Bot API response at all, e.g. an HTML error page from a
+// proxy, is reported with a snippet of it, so that it's never reported as an
+// error without any description.
+fn parse_response(status: u16, body: &[u8], ok_field: &str) -> Result<json::Value> {
+    let body = String::from_utf8_lossy(body);
+    match json::from_str::<json::Value>(&body) {
+        Ok(response) if response.get(ok_field).is_some_and(json::Value::is_boolean) => Ok(response),
+        _ => Err(Error::unexpected_response(Some(status), &body)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::TcpListener, thread, time::Duration};
+
+    use serde_json::json;
+
+    use super::*;
+
+    fn call(server_url: &str, client: reqwest::blocking::Client) -> Result<json::Value> {
+        HttpTransport::new(
+            Url::parse(server_url).unwrap(),
+            "token",
+            Some(client),
+            None,
+            ClientOptions::default(),
+        )
+        .unwrap()
+        .call("sendMessage", &json!({}))
+    }
+
+    #[test]
+    fn serialize() {
+        let transport = HttpTransport::new(
+            Url::parse("http://localhost").unwrap(),
+            "token",
+            None,
+            None,
+            ClientOptions::default(),
+        )
+        .unwrap();
+        let buf_ptr = transport.serialize(&json!({})).unwrap().as_ptr();
+
+        // Bodies already sent leave their space to the next ones.
+        for payload in [
+            json!({ "chat_id": -1001234567890i64, "text": "a long text with \"quotes\"" }),
+            json!({ "chat_id": "@my_channel", "text": "short" }),
+            json!({}),
+        ] {
+            let body = transport.serialize(&payload).unwrap();
+            assert_eq!(body, payload.to_string().into_bytes());
+            assert_eq!(body.as_ptr(), buf_ptr);
+        }
+
+        // Bodies in flight are not overwritten.
+        let in_flight = transport.serialize(&json!({ "text": "first" })).unwrap();
+        let body = transport.serialize(&json!({ "text": "second" })).unwrap();
+        assert_eq!(
+            in_flight,
+            json!({ "text": "first" }).to_string().into_bytes()
+        );
+        assert_eq!(body, json!({ "text": "second" }).to_string().into_bytes());
+        drop((in_flight, body));
+
+        // The space grown for an outlier is not kept.
+        let outlier = json!({ "text": "a".repeat(BODY_CAPACITY * 4) });
+        assert_eq!(
+            transport.serialize(&outlier).unwrap(),
+            outlier.to_string().into_bytes()
+        );
+        transport.serialize(&json!({})).unwrap();
+        assert!(transport.body.lock().unwrap().capacity() < BODY_CAPACITY * 2);
+    }
+
+    #[test]
+    fn max_request_bytes() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("POST", "/bottoken/sendMessage")
+            .with_body(r#"{"ok":true,"result":{}}"#)
+            .expect(1)
+            .create();
+        let mut transport = HttpTransport::new(
+            Url::parse(&server.url()).unwrap(),
+            "token",
+            None,
+            None,
+            ClientOptions::default(),
+        )
+        .unwrap();
+        let payload = json!({ "text": "a".repeat(100) });
+        let size = payload.to_string().len();
+
+        transport.set_max_request_bytes(size);
+        transport.call("sendMessage", &payload).unwrap();
+
+        // Not sent at all
+        transport.set_max_request_bytes(size - 1);
+        match transport.call("sendMessage", &payload) {
+            Err(Error::RequestTooLarge { size: s, limit }) => {
+                assert_eq!(s, size);
+                assert_eq!(limit, size - 1);
+            }
+            res => panic!("unexpected result: {res:?}"),
+        }
+        mock.assert();
+    }
+
+    #[test]
+    fn endpoint_redacted() {
+        for (server_url, expected) in [
+            (
+                "https://api.telegram.org",
+                "https://api.telegram.org/bot<token>/sendMessage",
+            ),
+            (
+                "http://localhost:8081/ignored?q=1",
+                "http://localhost:8081/bot<token>/sendMessage",
+            ),
+        ] {
+            let transport = HttpTransport::new(
+                Url::parse(server_url).unwrap(),
+                "1234567890:AbCdEfGhiJkLmNoPq1R2s3T4u5V6w7X8y9z",
+                None,
+                None,
+                ClientOptions::default(),
+            )
+            .unwrap();
+            assert_eq!(transport.endpoint_redacted("sendMessage"), expected);
+        }
+    }
+
+    #[test]
+    fn non_utf8_response() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("POST", "/bottoken/sendMessage")
+            .with_status(400)
+            .with_body(b"{\"ok\":false,\"description\":\"Bad Request: caf\xe9\"}")
+            .create();
+        let response = call(&server.url(), Default::default()).unwrap();
+        assert_eq!(
+            response,
+            json!({ "ok": false, "description": "Bad Request: caf\u{FFFD}" })
+        );
+        mock.assert();
+
+        let mock = server
+            .mock("POST", "/bottoken/sendMessage")
+            .with_status(502)
+            .with_body(b"Bad Gateway \xff\n")
+            .create();
+        match call(&server.url(), Default::default()) {
+            Err(Error::UnexpectedResponse {
+                status,
+                body_snippet,
+            }) => {
+                assert_eq!(status, Some(502));
+                assert_eq!(body_snippet, "Bad Gateway \u{FFFD}");
+            }
+            res => panic!("unexpected result: {res:?}"),
+        }
+        mock.assert();
+    }
+
+    #[test]
+    fn unexpected_response() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("POST", "/bottoken/sendMessage")
+            .with_status(502)
+            .with_header("content-type", "text/html")
+            .with_body("<html><body><h1>502 Bad Gateway</h1></body></html>")
+            .create();
+        match call(&server.url(), Default::default()) {
+            Err(Error::UnexpectedResponse {
+                status,
+                body_snippet,
+            }) => {
+                assert_eq!(status, Some(502));
+                assert_eq!(
+                    body_snippet,
+                    "<html><body><h1>502 Bad Gateway</h1></body></html>"
+                );
+            }
+            res => panic!("unexpected result: {res:?}"),
+        }
+        mock.assert();
+
+        // JSON, but not a Bot API response
+        let mock = server
+            .mock("POST", "/bottoken/sendMessage")
+            .with_body(r#"{"error":"not found"}"#)
+            .create();
+        match call(&server.url(), Default::default()) {
+            Err(Error::UnexpectedResponse {
+                status: Some(200), ..
+            }) => {}
+            res => panic!("unexpected result: {res:?}"),
+        }
+        mock.assert();
+    }
+
+    // Only Linux routes the whole `127.0.0.0/8` to loopback by default.
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn client_options() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let server_url = format!("http://{}", listener.local_addr().unwrap());
+        let server = thread::spawn(move || listener.accept().unwrap().1);
+
+        let local_address = "127.0.0.2".parse().unwrap();
+        let transport = HttpTransport::new(
+            Url::parse(&server_url).unwrap(),
+            "token",
+            None,
+            None,
+            ClientOptions {
+                tcp_nodelay: Some(true),
+                local_address: Some(local_address),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        // The server drops the connection without responding
+        _ = transport.call("sendMessage", &json!({}));
+        assert_eq!(server.join().unwrap().ip(), local_address);
+    }
+
+    #[test]
+    fn error_kind() {
+        let mut server = mockito::Server::new();
+        let _mock = server
+            .mock("POST", "/bottoken/sendMessage")
+            .with_chunked_body(|w| {
+                thread::sleep(Duration::from_millis(500));
+                w.write_all(b"{}")
+            })
+            .create();
+        let client = reqwest::blocking::Client::builder()
+            .timeout(Duration::from_millis(100))
+            .build()
+            .unwrap();
+        match call(&server.url(), client) {
+            Err(Error::SendRequest(err)) => {
+                assert!(err.is_timeout());
+                assert!(!err.is_connect());
+            }
+            res => panic!("unexpected result: {res:?}"),
+        }
+
+        // Nothing is listening on the port after the listener is dropped
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        match call(&format!("http://127.0.0.1:{port}"), Default::default()) {
+            Err(Error::SendRequest(err)) => {
+                assert!(err.is_connect());
+                assert!(!err.is_timeout());
+            }
+            res => panic!("unexpected result: {res:?}"),
+        }
+    }
+
+    #[test]
+    fn desensitize_errors() {
+        // Nothing is listening on the port after the listener is dropped
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        for desensitize in [true, false] {
+            let mut transport = HttpTransport::new(
+                Url::parse(&format!("http://127.0.0.1:{port}")).unwrap(),
+                "token",
+                None,
+                None,
+                ClientOptions::default(),
+            )
+            .unwrap();
+            transport.set_desensitize_errors(desensitize);
+            let err = transport.call("sendMessage", &json!({})).unwrap_err();
+            assert!(matches!(err, Error::SendRequest(_)));
+            assert_eq!(
+                err.to_string().contains("/bottoken/sendMessage"),
+                !desensitize,
+                "{err}"
+            );
+        }
+    }
+}